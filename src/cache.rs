@@ -0,0 +1,545 @@
+use std::{
+    ffi::OsString,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+use crate::{
+    events::{mup_scraper::MUP_DEPARTMENT, upcoming},
+    fetcher::Validators,
+    json_parser::Config,
+};
+
+/// File within a dataset directory storing when it was last refreshed (unix seconds).
+const TIMESTAMP_FILE: &str = "timestamp";
+
+/// Datasets are refreshed after a day unless another TTL is configured.
+const DEFAULT_TTL_HOURS: i64 = 24;
+
+/// Suffix of the directory a refresh downloads into.
+const STAGING_SUFFIX: &str = ".staging";
+
+/// Suffix of the last good snapshot that is kept after a refresh.
+const PREVIOUS_SUFFIX: &str = ".previous";
+
+/// Datasets that were already reported as stale, so the note is printed once per process.
+static REPORTED_STALE: Mutex<Vec<Dataset>> = Mutex::new(Vec::new());
+
+/// Returns the sibling of `target` with the suffix appended to its name, e.g. `eventdata.staging`.
+fn sibling(target: &Path, suffix: &str) -> PathBuf {
+    let mut name = target.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    target.with_file_name(name)
+}
+
+/// Returns the staging directory of a refresh of `target`.
+pub fn staging_dir(target: &Path) -> PathBuf {
+    sibling(target, STAGING_SUFFIX)
+}
+
+/// Returns the last good snapshot of `target`.
+pub fn previous_dir(target: &Path) -> PathBuf {
+    sibling(target, PREVIOUS_SUFFIX)
+}
+
+/// Refreshes the directory `target` without ever leaving it empty or half written.
+///
+/// `fill` downloads the new data into the staging directory (which does not exist yet),
+/// `validate` checks it. Only if both succeed the staging directory replaces `target`,
+/// the replaced data is kept as last good snapshot. On failure `target` is left untouched.
+pub fn refresh<E: From<io::Error>>(
+    target: &Path,
+    fill: impl FnOnce(&Path) -> Result<(), E>,
+    validate: impl FnOnce(&Path) -> Result<(), E>,
+) -> Result<(), E> {
+    recover(target)?;
+
+    let staging = staging_dir(target);
+    remove_if_exists(&staging)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if let Err(e) = fill(&staging).and_then(|_| validate(&staging)) {
+        let _ = remove_if_exists(&staging);
+        return Err(e);
+    }
+
+    swap(target, &staging).map_err(|e| {
+        let _ = remove_if_exists(&staging);
+        E::from(e)
+    })
+}
+
+/// Replaces `target` by `staging` and keeps the replaced data as last good snapshot.
+/// If the staging directory cannot be moved into place, the old data is moved back.
+fn swap(target: &Path, staging: &Path) -> io::Result<()> {
+    let previous = previous_dir(target);
+
+    if target.exists() {
+        remove_if_exists(&previous)?;
+        fs::rename(target, &previous)?;
+    }
+
+    if let Err(e) = fs::rename(staging, target) {
+        if previous.exists() {
+            fs::rename(&previous, target)?;
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Restores the last good snapshot if `target` is missing, e.g. after the process
+/// was killed between the two renames of a swap.
+pub fn recover(target: &Path) -> io::Result<()> {
+    let previous = previous_dir(target);
+    if !target.exists() && previous.exists() {
+        fs::rename(previous, target)?;
+    }
+    Ok(())
+}
+
+/// Copies a directory with all its contents.
+pub fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the directory contains a file with the extension in it or in one of its subdirectories,
+/// e.g. that a clone contains event files at all.
+pub fn contains_file_with_extension(path: &Path, extension: &str) -> io::Result<bool> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            if contains_file_with_extension(&entry.path(), extension)? {
+                return Ok(true);
+            }
+        } else if entry.path().extension().is_some_and(|ext| ext == extension) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Locally cached dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dataset {
+    Mensa,
+    Events,
+    /// Scraped MuP lectures, stored as department within the event data.
+    Mup,
+}
+
+impl Dataset {
+    pub const ALL: [Dataset; 3] = [Dataset::Mensa, Dataset::Events, Dataset::Mup];
+
+    pub fn from_str(s: &str) -> Option<Dataset> {
+        match s.trim().to_lowercase().as_str() {
+            "mensa" => Some(Dataset::Mensa),
+            "events" => Some(Dataset::Events),
+            "mup" => Some(Dataset::Mup),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Dataset::Mensa => "mensa",
+            Dataset::Events => "events",
+            Dataset::Mup => "mup",
+        }
+    }
+
+    /// Directory of the dataset within the cache directory.
+    pub fn path(&self, cache_dir: &Path) -> PathBuf {
+        match self {
+            Dataset::Mensa => cache_dir.join("mensadata"),
+            Dataset::Events => cache_dir.join("eventdata"),
+            Dataset::Mup => cache_dir.join("eventdata").join(MUP_DEPARTMENT),
+        }
+    }
+
+    /// Age after which the dataset is refreshed, configured with `cache ttl`.
+    pub fn ttl(&self) -> Duration {
+        Config::load_config()
+            .get_cache_ttls()
+            .into_iter()
+            .find(|(dataset, _)| Dataset::from_str(dataset) == Some(*self))
+            .and_then(|(_, ttl)| parse_duration(&ttl))
+            .unwrap_or(Duration::hours(DEFAULT_TTL_HOURS))
+    }
+}
+
+/// Whether a dataset has to be refreshed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The dataset was never downloaded.
+    Missing,
+    Fresh,
+    /// The dataset is older than its TTL.
+    Stale(Duration),
+}
+
+/// Returns the cache directory of the application.
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|cache_dir| cache_dir.join(env!("CARGO_PKG_NAME")))
+}
+
+/// Returns the directory of the dataset, restoring the last good snapshot and creating it if necessary.
+pub fn data_dir(dataset: Dataset, cache_dir: &Path) -> io::Result<PathBuf> {
+    let path = dataset.path(cache_dir);
+    recover(&path)?;
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// Returns when the data in the directory was last refreshed.
+pub fn last_refresh(data_path: &Path) -> Option<DateTime<Local>> {
+    let timestamp = fs::read_to_string(data_path.join(TIMESTAMP_FILE)).ok()?;
+    let last_refresh = DateTime::from_timestamp(timestamp.trim().parse().ok()?, 0)?;
+    Some(last_refresh.with_timezone(&Local))
+}
+
+/// Marks the data in the directory as refreshed now.
+pub fn write_timestamp(data_path: &Path) -> io::Result<()> {
+    fs::write(data_path.join(TIMESTAMP_FILE), Local::now().timestamp().to_string())
+}
+
+/// Checks whether the data of the dataset in the directory has to be refreshed.
+pub fn freshness(dataset: Dataset, data_path: &Path) -> Freshness {
+    match last_refresh(data_path) {
+        None => Freshness::Missing,
+        Some(last_refresh) => {
+            let age = Local::now() - last_refresh;
+            if age > dataset.ttl() {
+                Freshness::Stale(age)
+            } else {
+                Freshness::Fresh
+            }
+        }
+    }
+}
+
+/// Notes on stderr that stale data is used because it cannot be refreshed in offline mode.
+pub fn report_stale(dataset: Dataset, age: Duration) {
+    let Ok(mut reported) = REPORTED_STALE.lock() else {
+        return;
+    };
+    if reported.contains(&dataset) {
+        return;
+    }
+    reported.push(dataset);
+
+    eprintln!(
+        "Note: offline mode, the {} data was last refreshed {} ago and may be outdated",
+        dataset.as_str(),
+        upcoming::countdown(age.num_minutes())
+    );
+}
+
+/// State of a dataset on disk, shown by `cache status`.
+#[derive(Debug, Serialize)]
+pub struct DatasetStatus {
+    pub dataset: Dataset,
+    pub path: PathBuf,
+    pub last_refresh: Option<DateTime<Local>>,
+    pub stale: bool,
+    pub size_bytes: u64,
+    pub files: u64,
+    /// ETag of the downloaded archive or commit of the clone.
+    pub version: Option<String>,
+}
+
+impl fmt::Display for DatasetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(last_refresh) = self.last_refresh else {
+            return write!(f, "{:<8} not downloaded", self.dataset.as_str());
+        };
+
+        let age = upcoming::countdown((Local::now() - last_refresh).num_minutes().max(0));
+        write!(
+            f,
+            "{:<8} refreshed {} ago{}, {:.1} MB in {} files, version {}",
+            self.dataset.as_str(),
+            age,
+            if self.stale { " (stale)" } else { "" },
+            self.size_bytes as f64 / 1_000_000.0,
+            self.files,
+            self.version.as_deref().unwrap_or("unknown")
+        )
+    }
+}
+
+/// Returns the state of every dataset in the cache directory.
+pub fn status(cache_dir: &Path) -> io::Result<Vec<DatasetStatus>> {
+    Dataset::ALL
+        .iter()
+        .map(|dataset| {
+            let path = dataset.path(cache_dir);
+            let (size_bytes, files) = if path.exists() { dir_size(&path)? } else { (0, 0) };
+            let freshness = freshness(*dataset, &path);
+
+            Ok(DatasetStatus {
+                dataset: *dataset,
+                last_refresh: last_refresh(&path),
+                stale: matches!(freshness, Freshness::Stale(_)),
+                size_bytes,
+                files,
+                version: version(&path),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Removes the dataset including its snapshots. Returns whether anything was removed.
+pub fn clear(dataset: Dataset, cache_dir: &Path) -> io::Result<bool> {
+    let path = dataset.path(cache_dir);
+    let mut removed = false;
+    for dir in [staging_dir(&path), previous_dir(&path), path] {
+        removed |= dir.exists();
+        remove_if_exists(&dir)?;
+    }
+    Ok(removed)
+}
+
+/// Removes datasets last refreshed before `older_than` and snapshots last modified before it.
+/// Returns the removed directories.
+pub fn prune(cache_dir: &Path, older_than: Duration) -> io::Result<Vec<PathBuf>> {
+    let cutoff = Local::now() - older_than;
+    let mut removed = vec![];
+
+    for dataset in Dataset::ALL {
+        let path = dataset.path(cache_dir);
+
+        for snapshot in [staging_dir(&path), previous_dir(&path)] {
+            let modified = fs::metadata(&snapshot).and_then(|metadata| metadata.modified()).ok();
+            if modified.is_some_and(|modified| DateTime::<Local>::from(modified) < cutoff) {
+                remove_if_exists(&snapshot)?;
+                removed.push(snapshot);
+            }
+        }
+
+        if last_refresh(&path).is_some_and(|last_refresh| last_refresh < cutoff) {
+            remove_if_exists(&path)?;
+            removed.push(path);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Parses a duration like "30m", "12h", "7d" or "2w".
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit())?;
+    let amount: i64 = s[..unit_start].parse().ok()?;
+
+    match s[unit_start..].trim() {
+        "m" | "min" => Some(Duration::minutes(amount)),
+        "h" => Some(Duration::hours(amount)),
+        "d" => Some(Duration::days(amount)),
+        "w" => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+/// Formats a duration in the largest unit that represents it exactly, the inverse of `parse_duration`.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    match minutes {
+        m if m != 0 && m % (7 * 24 * 60) == 0 => format!("{}w", m / (7 * 24 * 60)),
+        m if m != 0 && m % (24 * 60) == 0 => format!("{}d", m / (24 * 60)),
+        m if m != 0 && m % 60 == 0 => format!("{}h", m / 60),
+        m => format!("{}m", m),
+    }
+}
+
+/// Returns the total size in bytes and the number of files in the directory.
+fn dir_size(path: &Path) -> io::Result<(u64, u64)> {
+    let mut size = (0, 0);
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let (bytes, files) = dir_size(&entry.path())?;
+            size = (size.0 + bytes, size.1 + files);
+        } else {
+            size = (size.0 + entry.metadata()?.len(), size.1 + 1);
+        }
+    }
+    Ok(size)
+}
+
+/// Version of the data: the ETag of the downloaded archive or the commit of a git clone.
+fn version(data_path: &Path) -> Option<String> {
+    if let Some(etag) = Validators::read(data_path).etag {
+        return Some(etag.trim_matches('"').trim_start_matches("W/").trim_matches('"').to_string());
+    }
+
+    let git_dir = data_path.join(".git");
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let commit = match head.trim().strip_prefix("ref: ") {
+        Some(reference) => fs::read_to_string(git_dir.join(reference)).ok()?,
+        None => head,
+    };
+    Some(commit.trim().chars().take(12).collect())
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_parser::isolate_config;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_refresh_swaps_and_keeps_previous() {
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("eventdata");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("old.json"), "[]").unwrap();
+
+        refresh::<io::Error>(&target, |staging| {
+            fs::create_dir_all(staging)?;
+            fs::write(staging.join("new.json"), "[]")
+        }, |_| Ok(()))
+        .unwrap();
+
+        assert!(target.join("new.json").exists());
+        assert!(!target.join("old.json").exists());
+        assert!(previous_dir(&target).join("old.json").exists());
+        assert!(!staging_dir(&target).exists());
+    }
+
+    #[test]
+    fn test_refresh_failure_keeps_data() {
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("eventdata");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("old.json"), "[]").unwrap();
+
+        let failed_fill = refresh(&target, |staging| {
+            fs::create_dir_all(staging)?;
+            Err(io::Error::other("offline"))
+        }, |_| Ok(()));
+        let failed_validation = refresh(&target, |staging| fs::create_dir_all(staging), |staging| {
+            match contains_file_with_extension(staging, "json")? {
+                true => Ok(()),
+                false => Err(io::Error::other("empty")),
+            }
+        });
+
+        assert!(failed_fill.is_err());
+        assert!(failed_validation.is_err());
+        assert!(target.join("old.json").exists());
+        assert!(!staging_dir(&target).exists());
+    }
+
+    #[test]
+    fn test_recover() {
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("mensadata");
+        fs::create_dir_all(previous_dir(&target)).unwrap();
+        fs::write(previous_dir(&target).join("timestamp"), "0").unwrap();
+
+        recover(&target).unwrap();
+
+        assert!(target.join("timestamp").exists());
+        assert!(!previous_dir(&target).exists());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("12h"), Some(Duration::hours(12)));
+        assert_eq!(parse_duration(" 7d "), Some(Duration::days(7)));
+        assert_eq!(parse_duration("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("7"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("3y"), None);
+        assert_eq!(format_duration(Duration::days(7)), "1w");
+        assert_eq!(format_duration(Duration::minutes(90)), "90m");
+    }
+
+    #[test]
+    fn test_freshness() {
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let path = Dataset::Mensa.path(temp_dir.path());
+        fs::create_dir_all(&path).unwrap();
+
+        assert_eq!(freshness(Dataset::Mensa, &path), Freshness::Missing);
+
+        write_timestamp(&path).unwrap();
+        assert_eq!(freshness(Dataset::Mensa, &path), Freshness::Fresh);
+
+        let last_week = Local::now() - Duration::weeks(1);
+        fs::write(path.join(TIMESTAMP_FILE), last_week.timestamp().to_string()).unwrap();
+        assert!(matches!(freshness(Dataset::Mensa, &path), Freshness::Stale(_)));
+    }
+
+    #[test]
+    fn test_status_and_clear() {
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let path = data_dir(Dataset::Events, temp_dir.path()).unwrap();
+        fs::write(path.join("module.json"), "[]").unwrap();
+        write_timestamp(&path).unwrap();
+        fs::create_dir_all(previous_dir(&path)).unwrap();
+
+        let status = status(temp_dir.path()).unwrap();
+        let events = status.iter().find(|status| status.dataset == Dataset::Events).unwrap();
+        let mensa = status.iter().find(|status| status.dataset == Dataset::Mensa).unwrap();
+
+        assert_eq!(events.files, 2);
+        assert!(events.last_refresh.is_some());
+        assert!(!events.stale);
+        assert!(mensa.last_refresh.is_none());
+
+        assert!(clear(Dataset::Events, temp_dir.path()).unwrap());
+        assert!(!path.exists());
+        assert!(!previous_dir(&path).exists());
+        assert!(!clear(Dataset::Events, temp_dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_prune() {
+        let temp_dir = tempdir().unwrap();
+        let mensa = data_dir(Dataset::Mensa, temp_dir.path()).unwrap();
+        let events = data_dir(Dataset::Events, temp_dir.path()).unwrap();
+        write_timestamp(&mensa).unwrap();
+        let last_month = Local::now() - Duration::weeks(4);
+        fs::write(events.join(TIMESTAMP_FILE), last_month.timestamp().to_string()).unwrap();
+
+        let removed = prune(temp_dir.path(), Duration::weeks(1)).unwrap();
+
+        assert_eq!(removed, vec![events.clone()]);
+        assert!(mensa.exists());
+        assert!(!events.exists());
+    }
+}
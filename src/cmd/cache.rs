@@ -0,0 +1,123 @@
+use crate::{
+    cache::{self, Dataset},
+    events::event_index::EventIndex,
+    json_parser::Config,
+};
+use clap::{Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+pub struct Cmd {
+    #[clap(subcommand)]
+    command: CacheCommands,
+
+    #[arg(short, long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommands {
+    /// Shows age, size and version of the cached data
+    Status,
+    /// Removes cached data, it is downloaded again when needed
+    Clear {
+        /// The data to remove (mensa, events, mup), all data if omitted
+        dataset: Option<String>,
+    },
+    /// Removes cached data and snapshots older than the given age
+    Prune {
+        /// The age, e.g. 30m, 12h, 7d or 2w
+        #[arg(long)]
+        older_than: String,
+    },
+    /// Shows or sets after which age cached data is refreshed
+    Ttl {
+        /// The data to configure (mensa, events, mup)
+        dataset: Option<String>,
+        /// The age, e.g. 30m, 12h, 7d or 2w
+        ttl: Option<String>,
+    },
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let cache_dir = cache::cache_dir().ok_or("Cache directory not found")?;
+
+        match self.command {
+            CacheCommands::Status => {
+                let status = cache::status(&cache_dir)?;
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&status)?);
+                } else {
+                    for dataset in status {
+                        println!("{}", dataset);
+                    }
+                }
+            }
+            CacheCommands::Clear { dataset } => {
+                let datasets = match dataset {
+                    Some(dataset) => vec![parse_dataset(&dataset)?],
+                    // MuP data lives within the event data and is removed with it
+                    None => vec![Dataset::Mensa, Dataset::Events],
+                };
+
+                for dataset in datasets {
+                    if cache::clear(dataset, &cache_dir)? {
+                        println!("Removed {} data", dataset.as_str());
+                    } else {
+                        println!("No {} data cached", dataset.as_str());
+                    }
+                    if dataset != Dataset::Mensa {
+                        EventIndex::remove(&cache_dir)?;
+                    }
+                }
+            }
+            CacheCommands::Prune { older_than } => {
+                let older_than = cache::parse_duration(&older_than)
+                    .ok_or_else(|| format!("Failed to parse age '{}'. Expected e.g. 30m, 12h, 7d or 2w", older_than))?;
+
+                let removed = cache::prune(&cache_dir, older_than)?;
+                // The index is outdated once event or MuP data is gone, snapshots are not indexed
+                if removed.iter().any(|path| path.starts_with(Dataset::Events.path(&cache_dir))) {
+                    EventIndex::remove(&cache_dir)?;
+                }
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&removed)?);
+                } else if removed.is_empty() {
+                    println!("Nothing to prune.");
+                } else {
+                    for path in removed {
+                        println!("Removed {}", path.display());
+                    }
+                }
+            }
+            CacheCommands::Ttl { dataset: None, .. } => {
+                for dataset in Dataset::ALL {
+                    println!("{:<8} {}", dataset.as_str(), cache::format_duration(dataset.ttl()));
+                }
+            }
+            CacheCommands::Ttl { dataset: Some(dataset), ttl } => {
+                let dataset = parse_dataset(&dataset)?;
+                match ttl {
+                    Some(ttl) => {
+                        cache::parse_duration(&ttl)
+                            .ok_or_else(|| format!("Failed to parse TTL '{}'. Expected e.g. 30m, 12h, 7d or 2w", ttl))?;
+                        println!("Setting {} TTL to: {}", dataset.as_str(), ttl);
+
+                        let mut cfg = Config::load_config();
+                        cfg.set_cache_ttl(dataset.as_str(), &ttl);
+                        Config::save_config_json(&cfg);
+                    }
+                    None => println!("{}", cache::format_duration(dataset.ttl())),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_dataset(dataset: &str) -> Result<Dataset, String> {
+    Dataset::from_str(dataset).ok_or_else(|| format!("Unknown data '{}'. Use 'mensa', 'events' or 'mup'", dataset))
+}
@@ -1,11 +1,25 @@
 use crate::events::event::EventMeta;
 use crate::events::{
+    changes,
+    conflicts,
+    error::EventError,
     event::Event,
+    event_index::EventIndex,
+    free_slots,
     haw_event::HawEventEntry,
+    ics_event::{IcsEvent, ICS_DEPARTMENT},
+    ics_export,
+    overrides::{self, Action, Override},
+    planner::{self, Criterion},
+    schedule,
+    search,
+    upcoming,
 };
 use crate::json_parser::Config;
-use chrono::NaiveDate;
-use clap::{Parser, Subcommand};
+use crate::semester;
+use chrono::{NaiveDate, NaiveTime};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 pub struct Cmd {
@@ -21,15 +35,42 @@ pub struct Cmd {
 
 #[derive(Subcommand, Debug)]
 enum EventCommands {
-    /// Get events for a specific date / module
+    /// Get events for a specific date / module or a date range
     Get {
         /// The date to get events for
-        date: String,
+        #[arg(required_unless_present = "from")]
+        date: Option<String>,
         module: Option<String>,
         department: Option<String>,
+        /// First day of the date range (YYYY-MM-DD)
+        #[arg(long, requires = "to", conflicts_with = "date")]
+        from: Option<String>,
+        /// Last day of the date range (YYYY-MM-DD)
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+    },
+    /// Show the events of the current week
+    Week {
+        /// Number of weeks to shift (e.g. 1 for next week, -1 for last week)
+        #[arg(short, long, default_value_t = 0, allow_hyphen_values = true)]
+        offset: i64,
+    },
+    /// Show the events running right now
+    Now {
+        /// Print a single line for shell prompts and status bars
+        #[arg(long)]
+        compact: bool,
+    },
+    /// Show the next event, where it takes place and when it starts
+    Next {
+        /// Print a single line for shell prompts and status bars
+        #[arg(long)]
+        compact: bool,
     },
     /// Fetches all event data and stores it in the cache directory.
     Cache,
+    /// Show schedule changes of the configured modules since the previous event data refresh
+    Changes,
     /// List departments that have events.
     ListDepartments,
     /// List modules for a specific department
@@ -37,12 +78,23 @@ enum EventCommands {
         /// The department to list modules for
         department: String,
     },
+    /// Search modules of all departments by name, event names and descriptions
+    Search {
+        /// The search query
+        query: String,
+        /// Number of results to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
     /// Add module to config
     Add {
         /// The module to add
         module: String,
         /// The department of the module
         department: String,
+        /// Refuse to add the module if it collides with already selected modules
+        #[arg(long)]
+        strict: bool,
     },
     /// Remove module from config
     Remove {
@@ -51,19 +103,139 @@ enum EventCommands {
         /// The department of the module
         department: String,
     },
+    /// Show overlapping events between the configured modules
+    Conflicts,
+    /// Show free time slots between the configured events
+    Free {
+        /// The date to show free slots for (YYYY-MM-DD, default today)
+        date: Option<String>,
+        /// Show the free slots of the whole week
+        #[arg(long)]
+        week: bool,
+        /// Minimum length of a free slot (e.g. 45m, 2h, 1h30m)
+        #[arg(long, default_value = "0m")]
+        min: String,
+        /// Time window to search in (HH:MM-HH:MM)
+        #[arg(long, default_value = "08:00-19:00")]
+        between: String,
+    },
+    /// Find a conflict-free combination of modules
+    Plan {
+        /// Module that must be part of every plan ("department:module")
+        #[arg(long = "require")]
+        required: Vec<String>,
+        /// Comma separated modules of which exactly one is chosen ("department:module,...")
+        #[arg(long = "choose")]
+        groups: Vec<String>,
+        /// Criteria to rank plans by after overlap
+        #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Criterion::Days, Criterion::Early])]
+        rank: Vec<Criterion>,
+        /// Events starting before this time count as early starts (HH:MM)
+        #[arg(long, default_value = "10:00")]
+        early_before: String,
+        /// Number of plans to show
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+        /// Apply the plan with the given rank to the config
+        #[arg(long)]
+        apply: Option<usize>,
+    },
+    /// Register a local .ics file as event source
+    AddIcs {
+        /// The name of the source
+        name: String,
+        /// The path of the .ics file
+        path: PathBuf,
+    },
+    /// Remove a registered .ics event source
+    RemoveIcs {
+        /// The name of the source
+        name: String,
+    },
+    /// Export all configured modules as an iCalendar (.ics) file
+    Export {
+        /// The path of the .ics file to write
+        #[arg(long)]
+        ics: PathBuf,
+    },
+    /// Change single occurrences of a configured module
+    Override {
+        #[clap(subcommand)]
+        command: OverrideCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum OverrideCommands {
+    /// Hide an occurrence
+    Remove {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+    },
+    /// Rename an occurrence
+    Rename {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+        /// The new name
+        name: String,
+    },
+    /// Move an occurrence to another time
+    Time {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+        /// The new start (YYYY-MM-DDTHH:MM)
+        new_start: String,
+        /// The new end (HH:MM on the day of the new start or YYYY-MM-DDTHH:MM)
+        new_end: String,
+    },
+    /// Change the location of an occurrence
+    Location {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+        /// The new location
+        location: String,
+    },
+    /// Attach a note to an occurrence
+    Note {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+        /// The note to attach
+        note: String,
+    },
+    /// List all overrides
+    List,
+    /// Remove all overrides of an occurrence
+    Reset {
+        #[clap(flatten)]
+        occurrence: Occurrence,
+    },
+}
+
+/// Single occurrence of a module, identified by its original start.
+#[derive(Args, Debug)]
+struct Occurrence {
+    /// The module of the occurrence ("department:module")
+    module: String,
+    /// The original start of the occurrence (YYYY-MM-DDTHH:MM)
+    start: String,
 }
 
 impl Cmd {
     pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         match self.command {
-            EventCommands::Get { date, module, department } => {
-                // Parse date string to NaiveDate
-                let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| {
-                    format!(
-                        "Failed to parse date '{}'. Expected format: YYYY-MM-DD",
-                        date
-                    )
-                })?;
+            EventCommands::Get { date, module, department, from, to } => {
+                // Date range query
+                if let (Some(from), Some(to)) = (from, to) {
+                    let from = parse_date(&from)?;
+                    let to = parse_date(&to)?;
+                    if from > to {
+                        return Err(format!("Start date {} is after end date {}", from, to).into());
+                    }
+
+                    return print_range(from, to, self.json);
+                }
+
+                let date = parse_date(&date.ok_or("Either a date or --from and --to must be given")?)?;
 
                 // Build Event_Meta vector (department is required, adjust as needed)
                 let mut event_meta = Vec::new();
@@ -89,28 +261,96 @@ impl Cmd {
                 }
 
                 // Call the trait method via fully qualified syntax
-                let events: Vec<HawEventEntry>;
-                if event_meta.is_empty() {
-                    // If no module or date is provided, default to config
-                    events = Event::get_all_events_for_date(date)?;
+                let events: Vec<HawEventEntry> = if event_meta.is_empty() {
+                    // If no module is provided, default to config (including ics sources)
+                    schedule::get_all_events_for_date(date)?
+                } else if event_meta[0].department == ICS_DEPARTMENT {
+                    let ics_events: Vec<IcsEvent> = Event::get_events_for_date(event_meta, date)?;
+                    ics_events.into_iter().map(HawEventEntry::from).collect()
                 } else {
                     // Use the provided module and date
-                    events = Event::get_events_for_date(event_meta, date)?;
-                }
+                    Event::get_events_for_date(event_meta, date)?
+                };
 
                 // Output events (as JSON or plain)
                 if self.json {
                     println!("{}", serde_json::to_string_pretty(&events)?);
                 } else {
+                    println!("{}", day_header(date));
                     for event in events {
                         println!("{}\n", event);
                     }
                 }
             }
+            EventCommands::Week { offset } => {
+                let (monday, sunday) = schedule::week_range(chrono::Local::now().date_naive(), offset);
+                print_range(monday, sunday, self.json)?;
+            }
+            EventCommands::Now { compact } => {
+                let now = chrono::Local::now().naive_local();
+                let events = schedule::get_events_in_range(now.date(), now.date())?;
+                let current = upcoming::current(&events, now);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&current)?);
+                } else if compact {
+                    // Status bars expect an empty line when there is nothing to show
+                    let lines: Vec<String> = current.iter().map(upcoming::CurrentEvent::compact).collect();
+                    println!("{}", lines.join(" | "));
+                } else if current.is_empty() {
+                    println!("No event right now.");
+                } else {
+                    for event in current {
+                        println!("{}\n", event);
+                    }
+                }
+            }
+            EventCommands::Next { compact } => {
+                let now = chrono::Local::now().naive_local();
+                let events = schedule::get_events_in_range(
+                    now.date(),
+                    now.date() + chrono::Duration::days(upcoming::LOOKAHEAD_DAYS),
+                )?;
+                let next = upcoming::next(&events, now);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&next)?);
+                } else if compact {
+                    println!("{}", next.map(|event| event.compact()).unwrap_or_default());
+                } else {
+                    match next {
+                        Some(event) => println!("{}", event),
+                        None => println!("No event in the next {} days.", upcoming::LOOKAHEAD_DAYS),
+                    }
+                }
+            }
             EventCommands::Cache => {
                 // Fetch all event data and store it in the cache directory
-                HawEventEntry::fetch_event_data(&HawEventEntry::get_cache_dir()?)?;
+                let fetch_start = chrono::Local::now().naive_local();
+                let cache_dir = HawEventEntry::get_cache_dir()?;
+                HawEventEntry::fetch_event_data(&cache_dir)?;
                 println!("Event data fetched and stored successfully.");
+
+                // Show changes detected by this refresh
+                let module_changes: Vec<changes::ModuleChanges> = changes::load_changes(&cache_dir, &EventMeta::from_config()?)?
+                    .into_iter()
+                    .filter(|module| module.detected >= fetch_start - chrono::Duration::seconds(1))
+                    .collect();
+                if !module_changes.is_empty() {
+                    println!();
+                    print_changes(&module_changes);
+                }
+            }
+            EventCommands::Changes => {
+                let module_changes = changes::load_changes(&HawEventEntry::get_cache_dir()?, &EventMeta::from_config()?)?;
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&module_changes)?);
+                } else if module_changes.is_empty() {
+                    println!("No schedule changes detected for the configured modules.");
+                } else {
+                    print_changes(&module_changes);
+                }
             }
             EventCommands::ListDepartments => {
                 // TODO: Use timecode val to reload event data if needed
@@ -142,15 +382,63 @@ impl Cmd {
                     }
                 }
             }
-            EventCommands::Add { module, department } => {
+            EventCommands::Search { query, limit } => {
+                // Search needs the event data of all departments
+                let cache_dir = HawEventEntry::get_cache_dir()?;
+                if HawEventEntry::get_departments().is_err() {
+                    HawEventEntry::fetch_event_data(&cache_dir)?;
+                    println!();
+                }
+
+                let index = EventIndex::load(&cache_dir)?;
+                let mut results = search::search(&index, &query)?;
+                results.truncate(limit);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&results)?);
+                } else if results.is_empty() {
+                    println!("No modules found for '{}'.", query);
+                } else {
+                    for result in results {
+                        println!("{}", result);
+                    }
+                }
+            }
+            EventCommands::Add { module, department, strict } => {
                 // Check if module is valid
                 let valid_modules = HawEventEntry::get_modules_for_department(&department, None)?;
 
                 if !valid_modules.contains(&module) {
-                    return Err(format!(
-                        "Invalid module '{}' for department '{}'",
-                        module, department
-                    ).into());
+                    return Err(EventError::InvalidModule { department, module }.into());
+                }
+
+                // Check for collisions with the already selected modules
+                let new_meta = EventMeta {
+                    department: department.clone(),
+                    module: module.clone(),
+                };
+                let mut event_descriptor = vec![new_meta.clone()];
+                event_descriptor.extend(EventMeta::from_config()?.into_iter().filter(|meta| *meta != new_meta));
+
+                let module_events = schedule::load_module_events(&event_descriptor)?;
+                let collisions: Vec<conflicts::ModuleConflict> = conflicts::find_conflicts(&module_events)
+                    .into_iter()
+                    .filter(|conflict| conflict.modules.0 == format!("{}:{}", department, module))
+                    .collect();
+
+                if !collisions.is_empty() {
+                    println!("Warning: module '{}' collides with already selected modules:\n", module);
+                    for conflict in &collisions {
+                        println!("{}", conflict);
+                    }
+
+                    if strict {
+                        return Err(format!(
+                            "Module '{}' was not added because it collides with {} module(s)",
+                            module,
+                            collisions.len()
+                        ).into());
+                    }
                 }
 
                 // Add a module to the config
@@ -168,7 +456,273 @@ impl Cmd {
                 cfg.remove_module(&module, &department)?;
                 Config::save_config_json(&cfg);
             }
+            EventCommands::Conflicts => {
+                let module_events = schedule::load_module_events(&EventMeta::from_config()?)?;
+                let module_conflicts = conflicts::find_conflicts(&module_events);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&module_conflicts)?);
+                } else if module_conflicts.is_empty() {
+                    println!("No conflicts between the configured modules.");
+                } else {
+                    for conflict in module_conflicts {
+                        println!("{}", conflict);
+                    }
+                }
+            }
+            EventCommands::Free { date, week, min, between } => {
+                let date = match date {
+                    Some(date) => parse_date(&date)?,
+                    None => chrono::Local::now().date_naive(),
+                };
+                let (from, to) = if week { schedule::week_range(date, 0) } else { (date, date) };
+                let between = free_slots::parse_time_window(&between)?;
+                let min = free_slots::parse_duration(&min)?;
+
+                let events = schedule::get_events_in_range(from, to)?;
+                let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+                let slots = free_slots::find_free_slots(&events, &days, between, min);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&slots)?);
+                    return Ok(());
+                }
+
+                for day in days {
+                    println!("{}", day_header(day));
+
+                    let day_slots: Vec<&free_slots::FreeSlot> = slots.iter().filter(|slot| slot.start.date() == day).collect();
+                    if day_slots.is_empty() {
+                        println!("No free slots");
+                    }
+                    for slot in day_slots {
+                        println!("{}", slot);
+                    }
+                    println!();
+                }
+            }
+            EventCommands::Plan { required, groups, rank, early_before, limit, apply } => {
+                let early_before = NaiveTime::parse_from_str(&early_before, "%H:%M")
+                    .map_err(|_| format!("Failed to parse time '{}'. Expected format: HH:MM", early_before))?;
+
+                let required_meta = required
+                    .iter()
+                    .map(|descriptor| EventMeta::from_descriptor(descriptor))
+                    .collect::<Result<Vec<EventMeta>, _>>()?;
+                let group_meta = groups
+                    .iter()
+                    .map(|group| {
+                        group
+                            .split(',')
+                            .map(|descriptor| EventMeta::from_descriptor(descriptor.trim()))
+                            .collect::<Result<Vec<EventMeta>, _>>()
+                    })
+                    .collect::<Result<Vec<Vec<EventMeta>>, _>>()?;
+
+                if required_meta.is_empty() && group_meta.is_empty() {
+                    return Err("No modules given. Use --require and --choose to select modules".into());
+                }
+
+                // Load cached event data for every module
+                let required_events = schedule::load_module_events(&required_meta)?;
+                let group_events = group_meta
+                    .iter()
+                    .map(|group| schedule::load_module_events(group))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let plans = planner::plan(&required_events, &group_events, &rank, early_before)?;
+
+                if let Some(apply) = apply {
+                    let plan = apply
+                        .checked_sub(1)
+                        .and_then(|index| plans.get(index))
+                        .ok_or_else(|| format!("No plan with rank {}", apply))?;
+
+                    let mut cfg = Config::load_config();
+
+                    // Replace other alternatives of the groups by the chosen modules
+                    for meta in group_meta.iter().flatten() {
+                        let _ = cfg.remove_module(&meta.module, &meta.department);
+                    }
+                    for descriptor in &plan.modules {
+                        let meta = EventMeta::from_descriptor(descriptor)?;
+                        if cfg.add_module(&meta.module, &meta.department).is_ok() {
+                            println!("Setting module '{}' in department '{}'...", meta.module, meta.department);
+                        }
+                    }
+
+                    Config::save_config_json(&cfg);
+                    println!("Applied plan #{}: {}", apply, plan.modules.join(", "));
+                    return Ok(());
+                }
+
+                let plans: Vec<&planner::Plan> = plans.iter().take(limit).collect();
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&plans)?);
+                } else {
+                    for (index, plan) in plans.iter().enumerate() {
+                        println!("#{}\n{}\n", index + 1, plan);
+                    }
+                }
+            }
+            EventCommands::AddIcs { name, path } => {
+                // Validate the file before storing it
+                let path = path.canonicalize()
+                    .map_err(|e| format!("Could not find ICS file '{}': {}", path.display(), e))?;
+                let ics_events = IcsEvent::parse_file(&path)?;
+
+                println!("Adding ICS source '{}' ({} events)...", name, ics_events.len());
+
+                let mut cfg = Config::load_config();
+                cfg.add_ics_source(&name, &path.to_string_lossy())?;
+                Config::save_config_json(&cfg);
+            }
+            EventCommands::RemoveIcs { name } => {
+                println!("Removing ICS source '{}'...", name);
+
+                let mut cfg = Config::load_config();
+                cfg.remove_ics_source(&name)?;
+                Config::save_config_json(&cfg);
+            }
+            EventCommands::Export { ics } => {
+                // Load events of all configured modules
+                let (calendar, count) = ics_export::configured_calendar()?;
+
+                if count == 0 {
+                    return Err("No events found for the configured modules".into());
+                }
+
+                ics_export::write_ics(&calendar, &ics)?;
+                println!("Exported {} events to {}", count, ics.display());
+            }
+            EventCommands::Override { command } => run_override(command, self.json)?,
         }
         Ok(())
     }
 }
+
+/// Prints all configured events between `from` and `to`, grouped by day.
+fn print_range(from: NaiveDate, to: NaiveDate, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let events = schedule::get_events_in_range(from, to)?;
+
+    if events.is_empty() {
+        return Err(format!("No events found between {} and {}", from, to).into());
+    }
+
+    let days = schedule::group_by_day(events);
+
+    // Output events (as JSON or plain)
+    if json {
+        println!("{}", serde_json::to_string_pretty(&days)?);
+        return Ok(());
+    }
+
+    // Holidays are listed even without events, so the gap in the schedule is explained
+    for day in from.iter_days().take_while(|day| *day <= to) {
+        let events = days.get(&day);
+        if events.is_none() && semester::holiday(day).is_none() {
+            continue;
+        }
+
+        println!("{}", day_header(day));
+        for event in events.into_iter().flatten() {
+            println!("{}\n", event);
+        }
+        if events.is_none() {
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the underlined header of a day with its lecture week or holiday.
+fn day_header(day: NaiveDate) -> String {
+    let header = format!("{} ({})", day.format("%A, %d.%m.%Y"), semester::describe_day(day));
+    format!("{}\n{}", header, "=".repeat(header.chars().count()))
+}
+
+/// Runs an `events override` subcommand.
+fn run_override(command: OverrideCommands, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (occurrence, action) = match command {
+        OverrideCommands::List => {
+            let event_overrides = overrides::from_entries(&Config::load_config().get_event_overrides());
+            if json {
+                println!("{}", serde_json::to_string_pretty(&event_overrides)?);
+            } else if event_overrides.is_empty() {
+                println!("No overrides configured.");
+            } else {
+                for event_override in event_overrides {
+                    println!("{}", event_override);
+                }
+            }
+            return Ok(());
+        }
+        OverrideCommands::Reset { occurrence } => {
+            let start = overrides::parse_datetime(&occurrence.start)?;
+            println!("Removing overrides of {} at {}...", occurrence.module, start);
+
+            let mut cfg = Config::load_config();
+            cfg.remove_event_overrides(&Override::occurrence_key(&occurrence.module, start))?;
+            Config::save_config_json(&cfg);
+            return Ok(());
+        }
+        OverrideCommands::Remove { occurrence } => (occurrence, Action::Remove),
+        OverrideCommands::Rename { occurrence, name } => (occurrence, Action::Rename(name)),
+        OverrideCommands::Time { occurrence, new_start, new_end } => {
+            let new_start = overrides::parse_datetime(&new_start)?;
+            let new_end = match NaiveTime::parse_from_str(&new_end, "%H:%M") {
+                Ok(time) => new_start.date().and_time(time),
+                Err(_) => overrides::parse_datetime(&new_end)?,
+            };
+            if new_end <= new_start {
+                return Err(format!("New end {} must be after the new start {}", new_end, new_start).into());
+            }
+            (occurrence, Action::Time(new_start, new_end))
+        }
+        OverrideCommands::Location { occurrence, location } => (occurrence, Action::Location(location)),
+        OverrideCommands::Note { occurrence, note } => (occurrence, Action::Note(note)),
+    };
+
+    // Overrides are keyed by the original start, so the occurrence has to exist in the event data
+    let meta = EventMeta::from_descriptor(&occurrence.module)?;
+    let start = overrides::parse_datetime(&occurrence.start)?;
+    let events = HawEventEntry::get_raw_events_for_module(&meta)?;
+    if !events.iter().any(|event| event.start == start) {
+        return Err(format!("Module '{}' has no occurrence starting at {}", occurrence.module, start).into());
+    }
+
+    let event_override = Override {
+        module: occurrence.module,
+        start,
+        action,
+    };
+    println!("Setting override: {}", event_override);
+
+    let mut cfg = Config::load_config();
+    cfg.set_event_override(&event_override.key(), event_override.to_entry());
+    Config::save_config_json(&cfg);
+
+    Ok(())
+}
+
+/// Prints the detected schedule changes per module.
+fn print_changes(module_changes: &[changes::ModuleChanges]) {
+    for module in module_changes {
+        println!("{} (changed {})", module.module, module.detected.format("%d.%m.%Y %H:%M"));
+        for change in &module.changes {
+            println!("  {}", change);
+        }
+        println!();
+    }
+}
+
+/// Parses a date in the format YYYY-MM-DD.
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+        format!(
+            "Failed to parse date '{}'. Expected format: YYYY-MM-DD",
+            date
+        )
+    })
+}
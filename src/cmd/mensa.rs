@@ -1,7 +1,9 @@
 use crate::cmd::mensa_settings;
+use crate::mensa::error::MealError;
 use crate::mensa::meal::Meal;
 use crate::mensa::haw_meal::HawMeal;
 use crate::json_parser::Config;
+use crate::fetcher;
 use clap::{Parser, Subcommand};
 
 #[derive(Debug, Parser)]
@@ -53,6 +55,7 @@ enum MensaCommands {
 impl Cmd {
     pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         let mut update_handle = None;
+        let mut result = Ok(());
 
         let currentdate = chrono::Local::now().date_naive();
 
@@ -61,8 +64,8 @@ impl Cmd {
             Some(MensaCommands::Today { .. })
             | Some(MensaCommands::Tomorrow { .. })
             | Some(MensaCommands::Date { .. }) => {
-                update_handle = Some(HawMeal::update_mensa_data());
-                self.date_command(&self.command, currentdate);
+                update_handle = (!fetcher::is_offline()).then(HawMeal::update_mensa_data);
+                result = self.date_command(&self.command, currentdate);
             }
             // Update/ Cache commands
             Some(MensaCommands::Update) | Some(MensaCommands::Cache) => {
@@ -77,8 +80,8 @@ impl Cmd {
             Some(MensaCommands::Settings(cmd)) => cmd.run()?,
             // Default case for today if no command is specified
             None => {
-                update_handle = Some(HawMeal::update_mensa_data());
-                self.date_command(&Some(MensaCommands::Today { number: self.number }), currentdate);
+                update_handle = (!fetcher::is_offline()).then(HawMeal::update_mensa_data);
+                result = self.date_command(&Some(MensaCommands::Today { number: self.number }), currentdate);
             }
         }
 
@@ -87,10 +90,10 @@ impl Cmd {
             let _ = handle.join();
         }
 
-        Ok(())
+        Ok(result?)
     }
 
-    fn date_command(&self, command: &Option<MensaCommands>, currentdate: chrono::NaiveDate) {
+    fn date_command(&self, command: &Option<MensaCommands>, currentdate: chrono::NaiveDate) -> Result<(), MealError> {
         // Determine the date to use based on the command
         let date_to_use = match &command {
             Some(MensaCommands::Today { .. }) => currentdate,
@@ -101,8 +104,7 @@ impl Cmd {
                 match chrono::NaiveDate::parse_from_str(date, "%d.%m.%Y") {
                     Ok(parsed_date) => parsed_date,
                     Err(_) => {
-                        println!("Invalid date format. Please use DD.MM.YYYY.");
-                        return;
+                        return Err(MealError::Other("Invalid date format. Please use DD.MM.YYYY.".to_string()));
                     }
                 }
             }
@@ -123,10 +125,9 @@ impl Cmd {
 
         // Check primary mensa
         if additional_mensa.is_none() {
-            mensa_name = config.get_primary_mensa().filter(|name| !name.is_empty()).unwrap_or_else(|| {
-                println!("Primary Mensa is not set - please set it in the config (cargo run mensa settings primary <name>)");
-                std::process::exit(1);
-            });
+            mensa_name = config.get_primary_mensa().filter(|name| !name.is_empty()).ok_or_else(|| {
+                MealError::Other("Primary Mensa is not set - please set it in the config (cargo run mensa settings primary <name>)".to_string())
+            })?;
         }
 
         // If an additional mensa is specified, use it
@@ -135,21 +136,18 @@ impl Cmd {
                 Some(list) => {
                     // Check if the index is valid (1-based index)
                     if *mensa_num < 1 || (*mensa_num as usize) > list.len() {
-                        println!("Invalid mensa number specified.");
-                        return;
+                        return Err(MealError::NotFound(format!("Mensa number {}", mensa_num)));
                     }
                 }
                 None => {
-                    println!("No additional mensas configured.");
-                    return;
+                    return Err(MealError::NotFound("Additional mensas".to_string()));
                 }
             }
 
             let mensa_list = match config.get_mensa_list() {
                 Some(list) => list,
                 None => {
-                    println!("No additional mensas configured.");
-                    return;
+                    return Err(MealError::NotFound("Additional mensas".to_string()));
             }};
             mensa_name = mensa_list
                 .get((*mensa_num - 1) as usize)
@@ -158,21 +156,7 @@ impl Cmd {
         }
 
         // Find the food for the specified date
-        let food_for_date:Vec<HawMeal> = match Meal::get_food_for_date(date_to_use, mensa_name.as_str()) {
-            Ok(food) => food,
-            Err(e) => {
-                if e.to_string().contains("404") {
-                    println!("No food found for mensa '{}' on date '{}'. This could be due to a holiday or weekend.", mensa_name, date_to_use);
-                    return;
-                }   
-
-                println!(
-                    "Error fetching food for mensa '{}' on date '{}': {}",
-                    mensa_name, date_to_use, e
-                );
-                return;
-            }
-        };
+        let food_for_date:Vec<HawMeal> = Meal::get_food_for_date(date_to_use, mensa_name.as_str())?;
 
         // Filter food items based on extras
         let food_for_date = match config.get_extras() {
@@ -182,8 +166,8 @@ impl Cmd {
 
         // If json option is set, print the food in JSON format
         if self.json {
-            println!("{}", serde_json::to_string(&food_for_date).unwrap());
-            return;
+            println!("{}", serde_json::to_string(&food_for_date)?);
+            return Ok(());
         }
 
         // output formatted date and food items
@@ -204,5 +188,6 @@ impl Cmd {
             }
         }
 
+        Ok(())
     }
 }
\ No newline at end of file
@@ -1,7 +1,11 @@
 use clap::{Parser, Subcommand};
+use crate::credentials;
+use crate::fetcher::{Backend, Provider, Source};
 use crate::json_parser::Config;
 use crate::json_parser::Extras;
 use crate::json_parser::Occupations;
+use crate::semester::Semester;
+use chrono::NaiveDate;
 use std::fs;
 
 #[derive(Debug, Parser)]
@@ -44,10 +48,49 @@ pub enum SettingsCommands {
         /// The username to set
         username: String,
     },
-    /// Sets the password for the MuP Plan site
+    /// Stores the password for the MuP Plan site encrypted, it is asked for without echo.
+    /// The environment variable CALENDARBOT_MUP_PASSWORD takes precedence
     Password {
-        /// The password to set
-        password: String,
+        /// Reads the password from the output of this command instead, e.g. "pass show haw/mup"
+        #[arg(long, conflicts_with = "reset")]
+        command: Option<String>,
+        /// Shows where the password is read from
+        #[arg(long, conflicts_with_all = ["command", "reset"])]
+        show: bool,
+        /// Removes the stored password and the password command
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Sets how mensa and event data is downloaded (archive: HTTP download, git: needs the git binary)
+    Backend {
+        /// The backend to use
+        backend: String,
+    },
+    /// Shows or sets where mensa and event data is downloaded from
+    Source {
+        /// The data to configure (mensa, events)
+        provider: String,
+        /// file:// directory, http(s):// mirror or github:owner/repository[@branch]
+        url: Option<String>,
+        /// Use the upstream HAWHHCalendarBot repository again
+        #[arg(long, conflicts_with = "url")]
+        reset: bool,
+    },
+    /// Only use the cached data and never download anything, like `--offline` for every command
+    Offline {
+        /// on or off
+        state: String,
+    },
+    /// Shows or sets the lecture period of the current semester, MuP lectures are repeated within it
+    Semester {
+        /// First day of lectures (YYYY-MM-DD)
+        #[arg(requires = "end")]
+        start: Option<String>,
+        /// Last day of lectures (YYYY-MM-DD)
+        end: Option<String>,
+        /// Use the computed HAW Hamburg lecture period again
+        #[arg(long, conflicts_with = "start")]
+        reset: bool,
     },
     /// Shows the Path to the Config.json file.
     Config,
@@ -135,25 +178,134 @@ impl Cmd {
                 Config::save_config_json(&cfg);
                 Ok(())
             }
-            SettingsCommands::Password { password } => {
-                println!("Setting Password to: {}", password);
+            SettingsCommands::Password { show: true, .. } => {
+                println!("Password source: {}", credentials::source(&Config::load_config()));
+                Ok(())
+            }
+            SettingsCommands::Password { reset: true, .. } => {
+                let mut cfg = Config::load_config();
+                cfg.remove_password_command();
+                cfg.remove_cleartext_password();
+                Config::save_config_json(&cfg);
+
+                if credentials::remove_store()? {
+                    println!("Removed the stored password");
+                } else {
+                    println!("No password stored");
+                }
+                Ok(())
+            }
+            SettingsCommands::Password { command: Some(command), .. } => {
+                println!("Reading the password from: {}", command);
 
                 let mut cfg = Config::load_config();
+                cfg.update_password_command(&command);
+                Config::save_config_json(&cfg);
 
-                //TODO: Passwort verschlüsselung einbauen
+                Ok(())
+            }
+            SettingsCommands::Password { .. } => {
+                let password = rpassword::prompt_password("MuP password: ")?;
+                if password.is_empty() {
+                    return Err("The password must not be empty".into());
+                }
+                let path = credentials::store_password(&password)?;
 
-                cfg.update_password(password);
+                // The store is only read without a password command or an old cleartext password
+                let mut cfg = Config::load_config();
+                cfg.remove_password_command();
+                cfg.remove_cleartext_password();
+                Config::save_config_json(&cfg);
 
+                println!("Stored the password encrypted in {}", path.display());
+                Ok(())
+            }
+
+            SettingsCommands::Backend { backend } => {
+                let backend = Backend::from_str(&backend)
+                    .ok_or_else(|| format!("Unknown backend '{}'. Use 'archive' or 'git'", backend))?;
+                println!("Setting download backend to: {}", backend.as_str());
+
+                let mut cfg = Config::load_config();
+                cfg.update_fetch_backend(backend.as_str());
                 Config::save_config_json(&cfg);
 
                 Ok(())
             }
 
+            SettingsCommands::Source { provider, url, reset } => {
+                let provider = Provider::from_str(&provider)
+                    .ok_or_else(|| format!("Unknown data '{}'. Use 'mensa' or 'events'", provider))?;
+                let mut cfg = Config::load_config();
+
+                match url {
+                    Some(url) => {
+                        Source::parse(&url)?;
+                        println!("Setting {} data source to: {}", provider.as_str(), url);
+                        cfg.set_data_source(provider.as_str(), &url);
+                        Config::save_config_json(&cfg);
+                    }
+                    None if reset => {
+                        cfg.remove_data_source(provider.as_str())?;
+                        println!("Using the default {} data source again.", provider.as_str());
+                        Config::save_config_json(&cfg);
+                    }
+                    None => println!("{} data source: {}", provider.as_str(), provider.source()),
+                }
+
+                Ok(())
+            }
+
+            SettingsCommands::Offline { state } => {
+                let offline = match state.trim().to_lowercase().as_str() {
+                    "on" | "true" => true,
+                    "off" | "false" => false,
+                    _ => return Err(format!("Unknown state '{}'. Use 'on' or 'off'", state).into()),
+                };
+                println!("Setting offline mode to: {}", if offline { "on" } else { "off" });
+
+                let mut cfg = Config::load_config();
+                cfg.update_offline(offline);
+                Config::save_config_json(&cfg);
+
+                Ok(())
+            }
+
+            SettingsCommands::Semester { start, end, reset } => {
+                let mut cfg = Config::load_config();
+
+                match (start, end) {
+                    (Some(start), Some(end)) => {
+                        let parse = |date: &str| {
+                            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                                .map_err(|_| format!("Failed to parse date '{}'. Expected format: YYYY-MM-DD", date))
+                        };
+                        let (start, end) = (parse(&start)?, parse(&end)?);
+                        if start > end {
+                            return Err(format!("Start date {} is after end date {}", start, end).into());
+                        }
+
+                        println!("Setting lecture period to: {} - {}", start, end);
+                        cfg.update_semester_dates(start, end);
+                        Config::save_config_json(&cfg);
+                    }
+                    _ if reset => {
+                        cfg.remove_semester_dates();
+                        println!("Using the computed lecture period again.");
+                        Config::save_config_json(&cfg);
+                    }
+                    _ => {
+                        let semester = Semester::upcoming(chrono::Local::now().date_naive());
+                        println!("{}: lectures {} - {}", semester, semester.lectures_start, semester.lectures_end);
+                    }
+                }
+
+                Ok(())
+            }
+
             SettingsCommands::Config {  } => {
 
-                let path = dirs::config_local_dir()
-                        .unwrap()
-                        .join("hawhhcalendarbot-cli/cfg.json");
+                let path = Config::config_dir().join("cfg.json");
 
                 match fs::read_to_string(&path) {
                     Ok(_) => println!("Config file is here: {}", path.display()),
@@ -165,9 +317,7 @@ impl Cmd {
             }
 
             SettingsCommands::Delet {  } => {
-                let path = dirs::config_local_dir()
-                            .unwrap()
-                            .join("hawhhcalendarbot-cli/cfg.json");
+                let path = Config::config_dir().join("cfg.json");
 
                 if path.exists() {
                     match fs::remove_file(&path) {
@@ -1,7 +1,15 @@
+use std::error::Error;
+
 use clap::{Parser, Subcommand};
 
+use crate::{events::error::EventError, fetcher, mensa::error::MealError};
+
 pub(crate) mod mensa;
 pub(crate) mod events;
+pub(crate) mod rooms;
+pub(crate) mod serve;
+pub(crate) mod cache;
+pub(crate) mod mup;
 pub mod mensa_settings;
 
 #[derive(Debug, Subcommand)]
@@ -10,6 +18,14 @@ pub enum Action {
     Mensa(mensa::Cmd),
     /// Shows the selected events
     Events(events::Cmd),
+    /// Shows free rooms and room schedules
+    Rooms(rooms::Cmd),
+    /// Serves the calendar and mensa data via a local HTTP server
+    Serve(serve::Cmd),
+    /// Shows and manages the locally cached data
+    Cache(cache::Cmd),
+    /// Tools for the plans of the MuP department
+    Mup(mup::Cmd),
 }
 
 #[derive(Debug, Parser)]
@@ -22,15 +38,38 @@ pub struct Cli {
     /// Whether to output the results in JSON format
     #[arg(long, short, default_value = "false", global = true)]
     json: bool,
+
+    /// Only use the cached data, never download anything
+    #[arg(long, global = true)]
+    offline: bool,
 }
 
 impl Cli {
     pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         use Action::*;
 
+        if self.offline {
+            fetcher::set_offline(true);
+        }
+
         match self.action {
             Mensa(cmd) => cmd.run(),
             Events(cmd) => cmd.run(),
+            Rooms(cmd) => cmd.run(),
+            Serve(cmd) => cmd.run(),
+            Cache(cmd) => cmd.run(),
+            Mup(cmd) => cmd.run(),
         }
     }
+}
+
+/// Maps an error to the exit code of the process, see "Exit codes" in the readme.
+pub fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if let Some(error) = error.downcast_ref::<EventError>() {
+        return error.exit_code();
+    }
+    if let Some(error) = error.downcast_ref::<MealError>() {
+        return error.exit_code();
+    }
+    1
 }
\ No newline at end of file
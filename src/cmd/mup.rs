@@ -0,0 +1,137 @@
+use std::{fs, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+    cache, credentials,
+    events::{
+        error::EventError,
+        mup_scraper::{MupGroup, MupLecture, MUP_DEPARTMENT},
+    },
+    fetcher,
+    json_parser::Config,
+};
+
+#[derive(Debug, Parser)]
+pub struct Cmd {
+    #[clap(subcommand)]
+    command: MupCommands,
+
+    #[arg(short, long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum MupCommands {
+    /// Parses a saved plan page without network access and reports the cells that could not be parsed
+    Parse {
+        /// The saved HTML page of a plan
+        file: PathBuf,
+    },
+    /// Lists the semester groups found on the plan index page, subscribed groups are marked with *
+    Groups {
+        /// Discovers the groups again instead of using the last found ones
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// Only fetches the plans of the subscribed groups instead of all of them
+    Subscribe {
+        /// The group, e.g. B_MT1 or b-mt1
+        group: String,
+    },
+    /// Stops fetching the plan of the group, all plans are fetched without subscriptions
+    Unsubscribe {
+        /// The group, e.g. B_MT1 or b-mt1
+        group: String,
+    },
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.command {
+            MupCommands::Parse { file } => {
+                let body = fs::read_to_string(&file)
+                    .map_err(|e| format!("Failed to read '{}': {}", file.display(), e))?;
+                let report = MupLecture::parse_html(&body);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                    return Ok(());
+                }
+
+                for cell in &report.cells {
+                    println!("{}", cell);
+                }
+                if report.diagnostics.is_empty() {
+                    println!("All {} lectures parsed.", report.cells.len());
+                } else {
+                    println!();
+                    println!("{} cells could not be parsed:", report.diagnostics.len());
+                    for diagnostic in &report.diagnostics {
+                        println!("  {}", diagnostic);
+                    }
+                }
+            }
+            MupCommands::Groups { refresh } => {
+                let groups = groups(refresh)?;
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&groups)?);
+                    return Ok(());
+                }
+
+                let subscribed = Config::load_config().get_mup_groups();
+                for group in groups {
+                    let mark = if subscribed.contains(&group.module()) { "*" } else { " " };
+                    println!("{} {:<12} {:<12} {}", mark, group.module(), group.id, group.name);
+                }
+                if subscribed.is_empty() {
+                    println!("No groups subscribed, the plans of all groups are fetched.");
+                }
+            }
+            MupCommands::Subscribe { group } => {
+                let module = MupLecture::module_name(&group);
+                let known = cache::cache_dir().and_then(|cache_dir| MupGroup::load_all(&cache_dir));
+                if known.is_some_and(|groups| !groups.iter().any(|group| group.module() == module)) {
+                    return Err(format!("Unknown group '{}'. See 'mup groups' for the available groups", group).into());
+                }
+
+                let mut cfg = Config::load_config();
+                cfg.add_mup_group(&module)?;
+                Config::save_config_json(&cfg);
+
+                println!("Subscribed to {}, it is fetched with the next refresh of the event data.", module);
+                println!("Add it to your events with: events add {} {}", module, MUP_DEPARTMENT);
+            }
+            MupCommands::Unsubscribe { group } => {
+                let module = MupLecture::module_name(&group);
+
+                let mut cfg = Config::load_config();
+                cfg.remove_mup_group(&module)?;
+                Config::save_config_json(&cfg);
+
+                println!("Unsubscribed from {}", module);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the groups of the last discovery, discovers them on the plan index page if there was none.
+fn groups(refresh: bool) -> Result<Vec<MupGroup>, Box<dyn std::error::Error>> {
+    let cache_dir = cache::cache_dir().ok_or("Cache directory not found")?;
+    if !refresh && let Some(groups) = MupGroup::load_all(&cache_dir) {
+        return Ok(groups);
+    }
+    if fetcher::is_offline() {
+        return Err(EventError::Offline.into());
+    }
+
+    let cfg = Config::load_config();
+    let user = cfg.get_username().filter(|user| !user.is_empty()).ok_or("No username set, see 'mensa settings username'")?;
+    let password = credentials::password(&cfg)?.ok_or("No password set, see 'mensa settings password'")?;
+
+    let groups = MupLecture::discover_groups(&user, &password)?;
+    MupGroup::save_all(&groups, &cache_dir)?;
+    Ok(groups)
+}
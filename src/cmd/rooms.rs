@@ -0,0 +1,97 @@
+use crate::events::{
+    event::Event,
+    event_index::EventIndex,
+    free_slots,
+    haw_event::HawEventEntry,
+    rooms,
+    schedule,
+};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+pub struct Cmd {
+    #[clap(subcommand)]
+    command: RoomCommands,
+
+    #[arg(short, long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum RoomCommands {
+    /// Find rooms without events in a time window
+    Free {
+        /// The date to search on (YYYY-MM-DD)
+        date: String,
+        /// The time window the room has to be free (HH:MM-HH:MM)
+        between: String,
+        /// Only show rooms of this building (e.g. BT7)
+        #[arg(long)]
+        building: Option<String>,
+    },
+    /// Show the events taking place in a room
+    Schedule {
+        /// The room (e.g. BT7-01.12)
+        room: String,
+        /// Show the whole current week instead of today
+        #[arg(long)]
+        week: bool,
+    },
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        // Room usage is derived from the events of all departments
+        let cache_dir = HawEventEntry::get_cache_dir()?;
+        if HawEventEntry::get_departments().is_err() {
+            HawEventEntry::fetch_event_data(&cache_dir)?;
+            println!();
+        }
+        let index = EventIndex::load(&cache_dir)?;
+
+        match self.command {
+            RoomCommands::Free { date, between, building } => {
+                let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .map_err(|_| format!("Failed to parse date '{}'. Expected format: YYYY-MM-DD", date))?;
+                let between = free_slots::parse_time_window(&between)?;
+
+                let events = index.events_in_range(date, date);
+                let free_rooms = rooms::find_free_rooms(index.rooms(), &events, date, between, building.as_deref());
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&free_rooms)?);
+                } else if free_rooms.is_empty() {
+                    println!("No free rooms found.");
+                } else {
+                    for room in free_rooms {
+                        println!("{}", room);
+                    }
+                }
+            }
+            RoomCommands::Schedule { room, week } => {
+                let today = chrono::Local::now().date_naive();
+                let (from, to) = if week { schedule::week_range(today, 0) } else { (today, today) };
+
+                let room_events = rooms::room_schedule(&index.room_events(&room), from, to);
+
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&schedule::group_by_day(room_events))?);
+                } else if room_events.is_empty() {
+                    println!("No events in room '{}'.", room);
+                } else {
+                    for (day, events) in schedule::group_by_day(room_events) {
+                        let header = day.format("%A, %d.%m.%Y").to_string();
+                        println!("{}\n{}", header, "=".repeat(header.len()));
+                        for event in events {
+                            println!("{} - {} {}", event.start.format("%H:%M"), event.end.format("%H:%M"), event.name);
+                        }
+                        println!();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
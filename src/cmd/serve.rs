@@ -0,0 +1,19 @@
+use crate::server;
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct Cmd {
+    /// The port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+
+    /// The address to bind to
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        server::serve(&self.host, self.port)
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    fmt, fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::json_parser::Config;
+
+/// Environment variable with the MuP password, takes precedence over every other source.
+pub const PASSWORD_ENV: &str = "CALENDARBOT_MUP_PASSWORD";
+
+/// Environment variable with the passphrase of the credential store, so it is not asked for.
+pub const PASSPHRASE_ENV: &str = "CALENDARBOT_PASSPHRASE";
+
+/// File within the config directory storing the encrypted MuP password.
+const STORE_FILE: &str = "credentials.json";
+
+#[derive(Debug)]
+pub enum CredentialError {
+    Io(io::Error),
+    /// No passphrase was given and none can be asked for (not a terminal).
+    Locked,
+    /// The passphrase does not decrypt the store.
+    WrongPassphrase,
+    /// The store file cannot be read.
+    Corrupt(String),
+    /// The password command failed or printed nothing.
+    Command(String),
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::Io(e) => write!(f, "{}", e),
+            CredentialError::Locked => write!(
+                f,
+                "The credential store is locked. Run the command in a terminal or set {}",
+                PASSPHRASE_ENV
+            ),
+            CredentialError::WrongPassphrase => write!(f, "Wrong passphrase for the credential store"),
+            CredentialError::Corrupt(reason) => write!(
+                f,
+                "The credential store is corrupt ({}). Set the password again with 'mensa settings password'",
+                reason
+            ),
+            CredentialError::Command(reason) => write!(f, "Password command failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
+impl From<io::Error> for CredentialError {
+    fn from(e: io::Error) -> Self {
+        CredentialError::Io(e)
+    }
+}
+
+/// Where the MuP password is read from, in the order they are tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordSource {
+    Env,
+    Command(String),
+    Store,
+    /// Password of an old config that was not migrated yet.
+    Cleartext,
+    None,
+}
+
+impl fmt::Display for PasswordSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordSource::Env => write!(f, "environment variable {}", PASSWORD_ENV),
+            PasswordSource::Command(command) => write!(f, "command '{}'", command),
+            PasswordSource::Store => write!(f, "encrypted credential store"),
+            PasswordSource::Cleartext => write!(f, "cleartext in cfg.json"),
+            PasswordSource::None => write!(f, "not set"),
+        }
+    }
+}
+
+/// MuP password encrypted with XChaCha20-Poly1305, the key is derived from a passphrase with Argon2id.
+/// All values are hex encoded.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Store {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Store {
+    pub fn encrypt(password: &str, passphrase: &str) -> Result<Store, CredentialError> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let ciphertext = cipher(passphrase, &salt)?
+            .encrypt(&nonce, password.as_bytes())
+            .map_err(|e| CredentialError::Corrupt(e.to_string()))?;
+
+        Ok(Store {
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<String, CredentialError> {
+        let decode = |value: &str| hex::decode(value).map_err(|e| CredentialError::Corrupt(e.to_string()));
+        let salt = decode(&self.salt)?;
+        let nonce = decode(&self.nonce)?;
+        let ciphertext = decode(&self.ciphertext)?;
+        if nonce.len() != 24 {
+            return Err(CredentialError::Corrupt("invalid nonce".to_string()));
+        }
+
+        let password = cipher(passphrase, &salt)?
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| CredentialError::WrongPassphrase)?;
+
+        String::from_utf8(password).map_err(|e| CredentialError::Corrupt(e.to_string()))
+    }
+
+    /// Reads the store, `None` if no password was stored yet.
+    pub fn load(path: &Path) -> Result<Option<Store>, CredentialError> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map(Some)
+                .map_err(|e| CredentialError::Corrupt(e.to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the store, only readable by the current user.
+    pub fn save(&self, path: &Path) -> Result<(), CredentialError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+        file.write_all(serde_json::to_string_pretty(self).map_err(io::Error::other)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Derives the key from the passphrase.
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, CredentialError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CredentialError::Corrupt(e.to_string()))?;
+
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// Returns the path of the credential store next to `cfg.json`.
+pub fn store_path() -> Option<PathBuf> {
+    Some(Config::config_dir().join(STORE_FILE))
+}
+
+/// Returns where the MuP password would be read from.
+pub fn source(cfg: &Config) -> PasswordSource {
+    if std::env::var(PASSWORD_ENV).is_ok_and(|password| !password.is_empty()) {
+        PasswordSource::Env
+    } else if let Some(command) = cfg.get_password_command() {
+        PasswordSource::Command(command)
+    } else if store_path().is_some_and(|path| path.exists()) {
+        PasswordSource::Store
+    } else if cfg.get_cleartext_password().is_some() {
+        PasswordSource::Cleartext
+    } else {
+        PasswordSource::None
+    }
+}
+
+/// Returns the MuP password from the environment, the password command or the credential store.
+/// Asks for the passphrase of the store if it is not set in the environment.
+/// A cleartext password of an old config is moved into the store on the way.
+pub fn password(cfg: &Config) -> Result<Option<String>, CredentialError> {
+    match source(cfg) {
+        PasswordSource::Env => Ok(std::env::var(PASSWORD_ENV).ok()),
+        PasswordSource::Command(command) => run_command(&command).map(Some),
+        PasswordSource::Store => {
+            let path = store_path().ok_or(CredentialError::Locked)?;
+            match Store::load(&path)? {
+                Some(store) => store.decrypt(&passphrase(false)?).map(Some),
+                None => Ok(None),
+            }
+        }
+        PasswordSource::Cleartext => {
+            migrate_cleartext();
+            Ok(cfg.get_cleartext_password())
+        }
+        PasswordSource::None => Ok(None),
+    }
+}
+
+/// Encrypts the password into the credential store, asks for a new passphrase.
+pub fn store_password(password: &str) -> Result<PathBuf, CredentialError> {
+    let path = store_path().ok_or(CredentialError::Locked)?;
+    Store::encrypt(password, &passphrase(true)?)?.save(&path)?;
+    Ok(path)
+}
+
+/// Removes the credential store, returns whether there was one.
+pub fn remove_store() -> Result<bool, CredentialError> {
+    let Some(path) = store_path() else {
+        return Ok(false);
+    };
+
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Moves a cleartext password of an old config into the credential store.
+/// Without a terminal or passphrase in the environment the password stays and a warning is printed.
+/// Only called where the password is needed, so other commands are never interrupted by the prompt.
+fn migrate_cleartext() {
+    let mut cfg = Config::load_config();
+    let Some(password) = cfg.get_cleartext_password() else {
+        return;
+    };
+
+    eprintln!("The MuP password is stored in cleartext in cfg.json, moving it into the encrypted credential store.");
+    match store_password(&password) {
+        Ok(path) => {
+            cfg.remove_cleartext_password();
+            Config::save_config_json(&cfg);
+            eprintln!("Stored the MuP password in {}", path.display());
+        }
+        Err(e) => eprintln!("Warning: the MuP password stays in cleartext: {}", e),
+    }
+}
+
+/// Returns the passphrase from the environment or asks for it without echo.
+fn passphrase(confirm: bool) -> Result<String, CredentialError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(CredentialError::Locked);
+    }
+
+    if !confirm {
+        return Ok(rpassword::prompt_password("Passphrase for the credential store: ")?);
+    }
+
+    let passphrase = rpassword::prompt_password("New passphrase for the credential store: ")?;
+    if passphrase.is_empty() {
+        return Err(CredentialError::Io(io::Error::other("The passphrase must not be empty")));
+    }
+    if rpassword::prompt_password("Repeat the passphrase: ")? != passphrase {
+        return Err(CredentialError::Io(io::Error::other("The passphrases do not match")));
+    }
+    Ok(passphrase)
+}
+
+/// Runs a `pass`-style command and returns the first line of its output.
+fn run_command(command: &str) -> Result<String, CredentialError> {
+    #[cfg(windows)]
+    let output = Command::new("cmd").args(["/C", command]).output()?;
+    #[cfg(not(windows))]
+    let output = Command::new("sh").args(["-c", command]).output()?;
+
+    if !output.status.success() {
+        return Err(CredentialError::Command(format!(
+            "'{}' exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .ok_or_else(|| CredentialError::Command(format!("'{}' printed no password", command)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STORE_FILE);
+
+        Store::encrypt("geheim", "passphrase").unwrap().save(&path).unwrap();
+        let store = Store::load(&path).unwrap().unwrap();
+
+        assert_eq!(store.decrypt("passphrase").unwrap(), "geheim");
+        assert!(matches!(store.decrypt("falsch"), Err(CredentialError::WrongPassphrase)));
+        assert!(!fs::read_to_string(&path).unwrap().contains("geheim"));
+    }
+
+    #[test]
+    fn test_missing_and_corrupt_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STORE_FILE);
+
+        assert!(Store::load(&path).unwrap().is_none());
+
+        fs::write(&path, "{}").unwrap();
+        assert!(matches!(Store::load(&path), Err(CredentialError::Corrupt(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_password_command() {
+        assert_eq!(run_command("printf 'geheim\\nzweite Zeile'").unwrap(), "geheim");
+        assert!(matches!(run_command("exit 1"), Err(CredentialError::Command(_))));
+        assert!(matches!(run_command("true"), Err(CredentialError::Command(_))));
+    }
+}
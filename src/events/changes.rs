@@ -0,0 +1,218 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::Dataset,
+    events::{event::EventMeta, haw_event::HawEventEntry},
+};
+
+/// Events of the same name that moved by at most this many days are reported as moved.
+const MAX_MOVE_DAYS: i64 = 7;
+
+/// Change of a single occurrence between two versions of a module.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Change {
+    Added { event: HawEventEntry },
+    Removed { event: HawEventEntry },
+    Moved { before: HawEventEntry, after: HawEventEntry },
+    Relocated { before: HawEventEntry, after: HawEventEntry },
+}
+
+impl Change {
+    fn start(&self) -> NaiveDateTime {
+        match self {
+            Change::Added { event } | Change::Removed { event } => event.start,
+            Change::Moved { before, .. } | Change::Relocated { before, .. } => before.start,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = |event: &HawEventEntry| {
+            format!("{} {}-{}", event.start.format("%a %d.%m.%Y"), event.start.format("%H:%M"), event.end.format("%H:%M"))
+        };
+
+        match self {
+            Change::Added { event } => write!(f, "+ {} {} ({})", time(event), event.name, event.location),
+            Change::Removed { event } => write!(f, "- {} {} ({})", time(event), event.name, event.location),
+            Change::Moved { before, after } => write!(
+                f,
+                "~ {} {} moved to {} ({})",
+                time(before),
+                before.name,
+                time(after),
+                after.location
+            ),
+            Change::Relocated { before, after } => write!(
+                f,
+                "~ {} {} changed room from {} to {}",
+                time(before),
+                before.name,
+                before.location,
+                after.location
+            ),
+        }
+    }
+}
+
+/// Detected changes of a configured module.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ModuleChanges {
+    /// Module in the format "department:module".
+    pub module: String,
+    /// Time the changes were detected.
+    pub detected: NaiveDateTime,
+    pub changes: Vec<Change>,
+}
+
+/// Version of a module before the last detected change.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    detected: i64,
+    previous: Vec<HawEventEntry>,
+}
+
+/// Compares two versions of a module's events.
+pub fn diff(old: &[HawEventEntry], new: &[HawEventEntry]) -> Vec<Change> {
+    let mut removed: Vec<&HawEventEntry> = old.iter().filter(|event| !new.contains(event)).collect();
+    let mut added: Vec<&HawEventEntry> = new.iter().filter(|event| !old.contains(event)).collect();
+    let mut changes = vec![];
+
+    // Same occurrence in a different room or with a different end
+    removed.retain(|before| {
+        match added.iter().position(|after| after.name == before.name && after.start == before.start) {
+            Some(index) => {
+                let after = added.remove(index);
+                changes.push(if after.end == before.end {
+                    Change::Relocated { before: (*before).clone(), after: after.clone() }
+                } else {
+                    Change::Moved { before: (*before).clone(), after: after.clone() }
+                });
+                false
+            }
+            None => true,
+        }
+    });
+
+    // Occurrence of the same name moved to another time, pick the closest one
+    removed.retain(|before| {
+        let closest = added
+            .iter()
+            .enumerate()
+            .filter(|(_, after)| after.name == before.name)
+            .map(|(index, after)| (index, (after.start - before.start).num_minutes().abs()))
+            .filter(|(_, minutes)| *minutes <= MAX_MOVE_DAYS * 24 * 60)
+            .min_by_key(|(_, minutes)| *minutes);
+
+        match closest {
+            Some((index, _)) => {
+                let after = added.remove(index);
+                changes.push(Change::Moved { before: (*before).clone(), after: after.clone() });
+                false
+            }
+            None => true,
+        }
+    });
+
+    changes.extend(removed.into_iter().map(|event| Change::Removed { event: event.clone() }));
+    changes.extend(added.into_iter().map(|event| Change::Added { event: event.clone() }));
+    changes.sort_by_key(|change| change.start());
+    changes
+}
+
+/// Reads the raw data of the given modules from the event data directory.
+/// Modules without data are skipped.
+pub fn read_modules(eventdata_path: &Path, event_descriptor: &[EventMeta]) -> Vec<(EventMeta, String)> {
+    event_descriptor
+        .iter()
+        .filter_map(|meta| Some((meta.clone(), fs::read_to_string(module_path(eventdata_path, meta)).ok()?)))
+        .collect()
+}
+
+/// Stores the previous version of every module whose data differs from the freshly fetched one.
+/// Unchanged modules keep their last snapshot, so the last change stays visible.
+/// Previous data that cannot be parsed is skipped with a warning.
+pub fn update_snapshots(
+    cache_dir: &Path,
+    eventdata_path: &Path,
+    previous: Vec<(EventMeta, String)>,
+) -> Result<(), Box<dyn Error>> {
+    let detected = Local::now().timestamp();
+
+    for (meta, old_content) in previous {
+        let new_content = fs::read_to_string(module_path(eventdata_path, &meta)).unwrap_or_else(|_| "[]".to_string());
+        if new_content == old_content {
+            continue;
+        }
+
+        let previous = match serde_json::from_str(&old_content) {
+            Ok(previous) => previous,
+            Err(e) => {
+                eprintln!(
+                    "Warning: cannot compare {}:{} with its previous data, skipping its changes: {}",
+                    meta.department, meta.module, e
+                );
+                continue;
+            }
+        };
+        let snapshot = Snapshot { detected, previous };
+
+        let path = module_path(&get_snapshot_dir(cache_dir), &meta);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&snapshot)?)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the changes of every given module that has a snapshot.
+pub fn load_changes(cache_dir: &Path, event_descriptor: &[EventMeta]) -> Result<Vec<ModuleChanges>, Box<dyn Error>> {
+    let eventdata_path = Dataset::Events.path(cache_dir);
+    let mut module_changes = vec![];
+
+    for meta in event_descriptor {
+        let Ok(snapshot_content) = fs::read_to_string(module_path(&get_snapshot_dir(cache_dir), meta)) else {
+            continue;
+        };
+        let snapshot: Snapshot = serde_json::from_str(&snapshot_content)?;
+
+        let current: Vec<HawEventEntry> = match fs::read_to_string(module_path(&eventdata_path, meta)) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(_) => vec![],
+        };
+
+        let changes = diff(&snapshot.previous, &current);
+        if changes.is_empty() {
+            continue;
+        }
+
+        module_changes.push(ModuleChanges {
+            module: format!("{}:{}", meta.department, meta.module),
+            detected: DateTime::from_timestamp(snapshot.detected, 0)
+                .ok_or("Invalid snapshot timestamp")?
+                .with_timezone(&Local)
+                .naive_local(),
+            changes,
+        });
+    }
+
+    Ok(module_changes)
+}
+
+fn get_snapshot_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("eventchanges")
+}
+
+fn module_path(base: &Path, meta: &EventMeta) -> PathBuf {
+    base.join(&meta.department).join(format!("{}.json", meta.module))
+}
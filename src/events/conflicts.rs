@@ -0,0 +1,142 @@
+use std::{collections::BTreeSet, fmt};
+
+use chrono::{Datelike, Duration};
+use serde::Serialize;
+
+use crate::events::{event::EventMeta, haw_event::HawEventEntry, schedule::ModuleEvents};
+
+/// Two overlapping occurrences of different modules.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+pub struct Overlap {
+    pub first: HawEventEntry,
+    pub second: HawEventEntry,
+    /// Overlap in minutes.
+    pub minutes: i64,
+}
+
+/// All overlaps between two modules.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ModuleConflict {
+    /// Modules in the format "department:module".
+    pub modules: (String, String),
+    /// Sum of all overlaps in minutes.
+    pub total_minutes: i64,
+    /// ISO weeks affected by the conflict, formatted as "YYYY-Www".
+    pub weeks: Vec<String>,
+    pub overlaps: Vec<Overlap>,
+}
+
+impl ModuleConflict {
+    fn new(modules: (String, String), mut overlaps: Vec<Overlap>) -> Self {
+        overlaps.sort_by_key(|overlap| (overlap.first.start, overlap.second.start));
+
+        let weeks = overlaps
+            .iter()
+            .map(|overlap| {
+                let week = overlap.first.start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            })
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+
+        ModuleConflict {
+            modules,
+            total_minutes: overlaps.iter().map(|overlap| overlap.minutes).sum(),
+            weeks,
+            overlaps,
+        }
+    }
+}
+
+impl fmt::Display for ModuleConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = Duration::minutes(self.total_minutes);
+        writeln!(
+            f,
+            "{} <-> {}: {} overlapping occurrences, {}h {:02}min in total",
+            self.modules.0,
+            self.modules.1,
+            self.overlaps.len(),
+            total.num_hours(),
+            total.num_minutes() % 60
+        )?;
+        writeln!(f, "Weeks: {}", self.weeks.join(", "))?;
+
+        for overlap in &self.overlaps {
+            writeln!(
+                f,
+                "  {} {}-{} {} / {}-{} {} ({} min)",
+                overlap.first.start.format("%a %d.%m.%Y"),
+                overlap.first.start.format("%H:%M"),
+                overlap.first.end.format("%H:%M"),
+                overlap.first.name,
+                overlap.second.start.format("%H:%M"),
+                overlap.second.end.format("%H:%M"),
+                overlap.second.name,
+                overlap.minutes
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds all overlapping occurrences between different modules.
+/// Conflicts are grouped by module pair, in the order the modules are given.
+pub fn find_conflicts(modules: &[ModuleEvents]) -> Vec<ModuleConflict> {
+    // Flatten all occurrences with the index of their module and sweep over them by start time
+    let mut occurrences: Vec<(usize, &HawEventEntry)> = modules
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (_, events))| events.iter().map(move |event| (index, event)))
+        .collect();
+    occurrences.sort_by_key(|(index, event)| (event.start, *index));
+
+    let mut pairs: Vec<((usize, usize), Vec<Overlap>)> = vec![];
+
+    for (i, (index_a, a)) in occurrences.iter().enumerate() {
+        for (index_b, b) in occurrences[i + 1..].iter() {
+            if b.start >= a.end {
+                break;
+            }
+            if index_a == index_b {
+                continue;
+            }
+
+            let minutes = (a.end.min(b.end) - b.start).num_minutes();
+            if minutes <= 0 {
+                continue;
+            }
+
+            // Keep the module order stable within each pair
+            let (key, first, second) = if index_a < index_b {
+                ((*index_a, *index_b), a, b)
+            } else {
+                ((*index_b, *index_a), b, a)
+            };
+            let overlap = Overlap {
+                first: (*first).clone(),
+                second: (*second).clone(),
+                minutes,
+            };
+
+            match pairs.iter_mut().find(|(pair, _)| *pair == key) {
+                Some((_, overlaps)) => overlaps.push(overlap),
+                None => pairs.push((key, vec![overlap])),
+            }
+        }
+    }
+
+    pairs.sort_by_key(|(pair, _)| *pair);
+    pairs
+        .into_iter()
+        .map(|((a, b), overlaps)| {
+            ModuleConflict::new((descriptor(&modules[a].0), descriptor(&modules[b].0)), overlaps)
+        })
+        .collect()
+}
+
+fn descriptor(meta: &EventMeta) -> String {
+    format!("{}:{}", meta.department, meta.module)
+}
@@ -0,0 +1,151 @@
+use std::{error::Error, fmt};
+
+use chrono::NaiveDate;
+
+use crate::{fetcher::FetchError, semester};
+
+/// Errors of the event subsystem.
+#[derive(Debug)]
+pub enum EventError {
+    /// A department, module, source or data directory does not exist.
+    NotFound(String),
+    /// The modules have no events on the date.
+    NoDataForDate { date: NaiveDate, modules: Vec<String> },
+    /// The module is not part of the department.
+    InvalidModule { department: String, module: String },
+    /// The local event data could not be read.
+    CacheCorrupt(String),
+    /// The event data could not be downloaded.
+    FetchFailed(String),
+    /// The login was rejected.
+    AuthFailed(String),
+    /// The event data is not cached and nothing may be downloaded in offline mode.
+    Offline,
+    Io(std::io::Error),
+    Other(String),
+}
+
+impl EventError {
+    /// Exit code of the CLI for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EventError::NotFound(_) => 3,
+            EventError::NoDataForDate { .. } => 4,
+            EventError::InvalidModule { .. } => 5,
+            EventError::CacheCorrupt(_) => 6,
+            EventError::FetchFailed(_) => 7,
+            EventError::AuthFailed(_) => 8,
+            EventError::Offline => 9,
+            EventError::Io(_) | EventError::Other(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::NotFound(what) => write!(f, "{} not found", what),
+            EventError::NoDataForDate { date, modules } => {
+                write!(f, "No events found for date {}", date)?;
+                if !modules.is_empty() {
+                    write!(f, " in modules: {}", modules.join(", "))?;
+                }
+                if let Some(holiday) = semester::holiday(*date) {
+                    write!(f, " (public holiday: {})", holiday.name)?;
+                }
+                Ok(())
+            }
+            EventError::InvalidModule { department, module } => write!(
+                f,
+                "Invalid module '{}' for department '{}'. Use `events search` to find modules",
+                module, department
+            ),
+            EventError::CacheCorrupt(reason) => write!(
+                f,
+                "Local event data is corrupt ({}). Run `events cache` to download it again",
+                reason
+            ),
+            EventError::FetchFailed(reason) => write!(
+                f,
+                "Could not download event data ({}). Check your network connection",
+                reason
+            ),
+            EventError::AuthFailed(reason) => write!(
+                f,
+                "Login failed ({}). Check your username and password in the config",
+                reason
+            ),
+            EventError::Offline => write!(
+                f,
+                "Event data is not available offline. Run once without --offline to download it"
+            ),
+            EventError::Io(e) => write!(f, "{}", e),
+            EventError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for EventError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EventError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for EventError {
+    fn from(e: std::io::Error) -> Self {
+        EventError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for EventError {
+    fn from(e: serde_json::Error) -> Self {
+        EventError::CacheCorrupt(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for EventError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        EventError::CacheCorrupt(e.to_string())
+    }
+}
+
+impl From<FetchError> for EventError {
+    fn from(e: FetchError) -> Self {
+        match e {
+            FetchError::Io(e) => EventError::Io(e),
+            FetchError::Failed(reason) => EventError::FetchFailed(reason),
+            FetchError::Offline => EventError::Offline,
+        }
+    }
+}
+
+impl From<reqwest::Error> for EventError {
+    fn from(e: reqwest::Error) -> Self {
+        EventError::FetchFailed(e.to_string())
+    }
+}
+
+impl From<String> for EventError {
+    fn from(message: String) -> Self {
+        EventError::Other(message)
+    }
+}
+
+impl From<&str> for EventError {
+    fn from(message: &str) -> Self {
+        EventError::Other(message.to_string())
+    }
+}
+
+/// Keeps event errors that were passed through `Box<dyn Error>` helpers.
+impl From<Box<dyn Error>> for EventError {
+    fn from(e: Box<dyn Error>) -> Self {
+        match e.downcast::<EventError>() {
+            Ok(e) => *e,
+            Err(e) => EventError::Other(e.to_string()),
+        }
+    }
+}
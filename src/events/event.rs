@@ -1,12 +1,16 @@
-use std::error::Error;
 use std::path::PathBuf;
 
-use chrono::NaiveDate;
-use dirs::cache_dir;
-use std::fs;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{
+    cache::{self, Dataset},
+    events::error::EventError,
+    fetcher,
+    json_parser::Config,
+};
 
 /// Event describing a module within a department.
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct EventMeta {
     /// The department name.
     pub department: String,
@@ -14,59 +18,117 @@ pub struct EventMeta {
     pub module: String,
 }
 
+impl EventMeta {
+    /// Parses a descriptor in the config format "department:module".
+    pub fn from_descriptor(descriptor: &str) -> Result<EventMeta, EventError> {
+        let (department, module) = descriptor
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid event descriptor format '{}'", descriptor))?;
+
+        Ok(EventMeta {
+            department: department.to_string(),
+            module: module.to_string(),
+        })
+    }
+
+    /// Returns the descriptors of all modules configured in the config.
+    pub fn from_config() -> Result<Vec<EventMeta>, EventError> {
+        let config = Config::load_config();
+        let event_descriptors = config
+            .get_events()
+            .ok_or("No event descriptors found in config")?;
+
+        event_descriptors
+            .iter()
+            .map(|descriptor| Self::from_descriptor(descriptor))
+            .collect()
+    }
+}
+
 /// Trait for event data sources, providing methods for loading, fetching, and caching event data.
 pub trait Event: Sized {
 
+    /// Returns the start of the event.
+    fn get_start(&self) -> NaiveDateTime;
+
+    /// Returns the descriptors of all modules configured for this event source.
+    fn get_configured_modules() -> Result<Vec<EventMeta>, EventError>;
+
     /// Return all events configured in config for a given date.
-    fn get_all_events_for_date(date: NaiveDate) -> Result<Vec<Self>, Box<dyn Error>>;
+    fn get_all_events_for_date(date: NaiveDate) -> Result<Vec<Self>, EventError>;
 
     /// Returns all events for a module in a given Department.
-    /// Attempts to load from local cache, falling back to remote fetch if unavailable.
-    fn get_events_for_module(event: &EventMeta) -> Result<Vec<Self>, Box<dyn Error>> {
+    /// Implementations may adjust the events, e.g. by applying overrides.
+    fn get_events_for_module(event: &EventMeta) -> Result<Vec<Self>, EventError> {
+        Self::get_raw_events_for_module(event)
+    }
+
+    /// Returns all events for a module in a given Department exactly as stored in the event data.
+    /// Attempts to load from local cache, falling back to remote fetch if unavailable and not offline.
+    fn get_raw_events_for_module(event: &EventMeta) -> Result<Vec<Self>, EventError> {
         let cache_dir = Self::get_cache_dir()?;
-        Self::load_from_local(event, cache_dir).or_else(|_| Self::fetch_events_for_module(event, &event.department))
+        let local = Self::load_from_local(event, cache_dir);
+        if fetcher::is_offline() {
+            return local;
+        }
+        local.or_else(|_| Self::fetch_events_for_module(event, &event.department))
     }
 
     /// Returns all events for the given descriptors on a specific date.
     fn get_events_for_date(
         event_descriptor: Vec<EventMeta>,
         date: NaiveDate,
-    ) -> Result<Vec<Self>, Box<dyn Error>>;
+    ) -> Result<Vec<Self>, EventError>;
+
+    /// Returns all configured events starting between `from` and `to` (both inclusive), sorted by start time.
+    fn get_events_in_range(from: NaiveDate, to: NaiveDate) -> Result<Vec<Self>, EventError> {
+        Self::get_module_events_in_range(&Self::get_configured_modules()?, from, to)
+    }
+
+    /// Returns all events of the given modules starting between `from` and `to` (both inclusive), sorted by start time.
+    fn get_module_events_in_range(
+        event_descriptor: &[EventMeta],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Self>, EventError> {
+        let mut events: Vec<Self> = vec![];
+        for module in event_descriptor.iter() {
+            let mut module_events = Self::get_events_for_module(module)?;
+            module_events.retain(|event| (from..=to).contains(&event.get_start().date()));
+            events.extend(module_events);
+        }
+
+        events.sort_by_key(|event| event.get_start());
+        Ok(events)
+    }
 
     /// List all possible modules for a given department.
-    fn get_modules_for_department(department: &str, filter: Option<&str>) -> Result<Vec<String>, Box<dyn Error>>;
+    fn get_modules_for_department(department: &str, filter: Option<&str>) -> Result<Vec<String>, EventError>;
 
     /// List all departments that have events.
-    fn get_departments() -> Result<Vec<String>, Box<dyn Error>>;
+    fn get_departments() -> Result<Vec<String>, EventError>;
 
     /// Loads events for a module from the local cache directory.
-    fn load_from_local(event: &EventMeta, cache_dir: PathBuf) -> Result<Vec<Self>, Box<dyn Error>>
+    fn load_from_local(event: &EventMeta, cache_dir: PathBuf) -> Result<Vec<Self>, EventError>
     where
         Self: Sized;
 
     /// Fetches events for a single module from a remote source.
-    fn fetch_events_for_module(event: &EventMeta, department: &str) -> Result<Vec<Self>, Box<dyn Error>>
+    fn fetch_events_for_module(event: &EventMeta, department: &str) -> Result<Vec<Self>, EventError>
     where
         Self: Sized;
 
     /// Fetches all event data and stores it in the cache directory.
-    fn fetch_event_data(cache_dir: &PathBuf) -> Result<(), Box<dyn Error>>;
+    fn fetch_event_data(cache_dir: &PathBuf) -> Result<(), EventError>;
 
     /// Returns the cache directory path for event data.
-    fn get_cache_dir() -> Result<std::path::PathBuf, Box<dyn Error>> {
-        let cache_dir = cache_dir().ok_or("Could not find cache directory")?;
-        Ok(cache_dir.join(env!("CARGO_PKG_NAME")))
+    fn get_cache_dir() -> Result<std::path::PathBuf, EventError> {
+        cache::cache_dir().ok_or_else(|| EventError::NotFound("Cache directory".to_string()))
     }
 
     /// Returns the directory path for event data within the cache.
     /// Creates the directory if it does not exist.
-    fn get_eventdata_dir(cache_dir: &PathBuf) -> Result<std::path::PathBuf, Box<dyn Error>> {
-        let eventdata_path = cache_dir.join("eventdata");
-
-        if !eventdata_path.exists() {
-            fs::create_dir_all(&eventdata_path).expect("Could not create event data directory");
-        }
-
-        Ok(eventdata_path)
+    fn get_eventdata_dir(cache_dir: &PathBuf) -> Result<std::path::PathBuf, EventError> {
+        Ok(cache::data_dir(Dataset::Events, cache_dir)?)
     }
 }
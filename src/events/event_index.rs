@@ -0,0 +1,252 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::Dataset,
+    events::{event::EventMeta, haw_event::HawEventEntry, rooms},
+};
+
+/// Version of the index format, indices with another version are rebuilt.
+const INDEX_VERSION: u32 = 2;
+
+/// File name of the index within the cache directory.
+const INDEX_FILE: &str = "eventindex.bin";
+
+/// Index loaded by this process, together with the cache directory it belongs to.
+static LOADED: Mutex<Option<(PathBuf, Arc<EventIndex>)>> = Mutex::new(None);
+
+/// Module with the position of its events in the index.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct IndexedModule {
+    department: String,
+    module: String,
+    /// Range of the module's events in `EventIndex::events`.
+    first: u32,
+    end: u32,
+    /// The module file could not be parsed, the range is empty.
+    corrupt: bool,
+}
+
+/// Compact binary index over the event data of all departments.
+/// Events are stored grouped by module, with lookup tables by start and room.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventIndex {
+    version: u32,
+    /// Modules sorted by department and module name.
+    modules: Vec<IndexedModule>,
+    events: Vec<HawEventEntry>,
+    /// Positions in `events` sorted by start.
+    by_start: Vec<u32>,
+    /// Positions in `events` per normalized room name.
+    by_room: BTreeMap<String, Vec<u32>>,
+}
+
+impl EventIndex {
+    /// Builds the index from the `<department>/<module>.json` files in the event data directory.
+    /// Files that cannot be parsed are reported and listed without events.
+    pub fn build(eventdata_path: &Path) -> Result<EventIndex, Box<dyn Error>> {
+        let mut module_files = vec![];
+        for department in fs::read_dir(eventdata_path)? {
+            let department = department?;
+            let department_name = department.file_name().to_string_lossy().to_string();
+            if !department.file_type()?.is_dir() || department_name.starts_with('.') {
+                continue;
+            }
+
+            for module in fs::read_dir(department.path())? {
+                let path = module?.path();
+                let Some(module_name) = path.file_name().and_then(|name| name.to_str()?.strip_suffix(".json")) else {
+                    continue;
+                };
+                if !module_name.starts_with('.') {
+                    module_files.push((department_name.clone(), module_name.to_string(), path.clone()));
+                }
+            }
+        }
+        module_files.sort();
+
+        let mut modules = vec![];
+        let mut events: Vec<HawEventEntry> = vec![];
+        for (department, module, path) in module_files {
+            let first = events.len() as u32;
+            let corrupt = match serde_json::from_str::<Vec<HawEventEntry>>(&fs::read_to_string(&path)?) {
+                Ok(mut module_events) => {
+                    module_events.sort_by_key(|event| event.start);
+                    events.extend(module_events);
+                    false
+                }
+                Err(e) => {
+                    eprintln!("Warning: cannot read the events of '{}': {}", path.display(), e);
+                    true
+                }
+            };
+
+            modules.push(IndexedModule {
+                department,
+                module,
+                first,
+                end: events.len() as u32,
+                corrupt,
+            });
+        }
+
+        let mut by_start: Vec<u32> = (0..events.len() as u32).collect();
+        by_start.sort_by_key(|position| events[*position as usize].start);
+
+        let mut by_room: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for &position in &by_start {
+            for room in rooms::rooms_of(&events[position as usize].location) {
+                by_room.entry(room).or_default().push(position);
+            }
+        }
+
+        Ok(EventIndex {
+            version: INDEX_VERSION,
+            modules,
+            events,
+            by_start,
+            by_room,
+        })
+    }
+
+    /// Builds the index for the event data in the cache directory and stores it next to it.
+    pub fn rebuild(cache_dir: &Path) -> Result<Arc<EventIndex>, Box<dyn Error>> {
+        let index = Arc::new(Self::build(&Dataset::Events.path(cache_dir))?);
+        fs::write(cache_dir.join(INDEX_FILE), bincode::serialize(index.as_ref())?)?;
+
+        *LOADED.lock().map_err(|_| "Event index lock poisoned")? = Some((cache_dir.to_path_buf(), index.clone()));
+        Ok(index)
+    }
+
+    /// Removes the stored index of the cache directory, e.g. after its event data was cleared.
+    pub fn remove(cache_dir: &Path) -> Result<(), Box<dyn Error>> {
+        let path = cache_dir.join(INDEX_FILE);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        *LOADED.lock().map_err(|_| "Event index lock poisoned")? = None;
+        Ok(())
+    }
+
+    /// Returns the index of the cache directory. It is read from disk once per process
+    /// and rebuilt if it is missing or outdated.
+    pub fn load(cache_dir: &Path) -> Result<Arc<EventIndex>, Box<dyn Error>> {
+        let loaded = LOADED.lock().map_err(|_| "Event index lock poisoned")?.clone();
+        if let Some((_, index)) = loaded.filter(|(path, _)| path == cache_dir) {
+            return Ok(index);
+        }
+
+        let stored = fs::read(cache_dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<EventIndex>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION);
+
+        match stored {
+            Some(index) => {
+                let index = Arc::new(index);
+                *LOADED.lock().map_err(|_| "Event index lock poisoned")? = Some((cache_dir.to_path_buf(), index.clone()));
+                Ok(index)
+            }
+            None => Self::rebuild(cache_dir),
+        }
+    }
+
+    /// Returns all departments with at least one module.
+    pub fn departments(&self) -> Vec<String> {
+        let mut departments: Vec<String> = self.modules.iter().map(|module| module.department.clone()).collect();
+        departments.dedup();
+        departments
+    }
+
+    /// Returns all modules of a department.
+    pub fn modules(&self, department: &str) -> Vec<String> {
+        self.modules
+            .iter()
+            .filter(|module| module.department == department)
+            .map(|module| module.module.clone())
+            .collect()
+    }
+
+    /// Returns the events of a module sorted by start, or `None` if the module is unknown.
+    pub fn module_events(&self, meta: &EventMeta) -> Option<&[HawEventEntry]> {
+        let module = self.find(meta)?;
+
+        Some(&self.events[module.first as usize..module.end as usize])
+    }
+
+    /// Returns whether the file of the module could not be parsed when the index was built.
+    pub fn is_corrupt(&self, meta: &EventMeta) -> bool {
+        self.find(meta).is_some_and(|module| module.corrupt)
+    }
+
+    fn find(&self, meta: &EventMeta) -> Option<&IndexedModule> {
+        let position = self
+            .modules
+            .binary_search_by(|module| {
+                (module.department.as_str(), module.module.as_str()).cmp(&(meta.department.as_str(), meta.module.as_str()))
+            })
+            .ok()?;
+
+        Some(&self.modules[position])
+    }
+
+    /// Returns the events of all modules starting between `from` and `to` (both inclusive), sorted by start.
+    /// Events listed in several modules are only returned once.
+    pub fn events_in_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<&HawEventEntry> {
+        let first = self.by_start.partition_point(|position| self.events[*position as usize].start.date() < from);
+        let end = self.by_start.partition_point(|position| self.events[*position as usize].start.date() <= to);
+
+        self.unique(&self.by_start[first..end])
+    }
+
+    /// Returns all known rooms.
+    pub fn rooms(&self) -> impl Iterator<Item = &String> {
+        self.by_room.keys()
+    }
+
+    /// Returns the events taking place in the room, sorted by start.
+    pub fn room_events(&self, room: &str) -> Vec<&HawEventEntry> {
+        self.by_room
+            .get(&room.trim().to_uppercase())
+            .map(|positions| self.unique(positions))
+            .unwrap_or_default()
+    }
+
+    /// Returns department, module name and events of every module.
+    pub fn iter_modules(&self) -> impl Iterator<Item = (&str, &str, &[HawEventEntry])> {
+        self.modules.iter().map(|module| {
+            (
+                module.department.as_str(),
+                module.module.as_str(),
+                &self.events[module.first as usize..module.end as usize],
+            )
+        })
+    }
+
+    /// Resolves positions sorted by start, skipping duplicates of the same occurrence.
+    fn unique(&self, positions: &[u32]) -> Vec<&HawEventEntry> {
+        let mut events: Vec<&HawEventEntry> = vec![];
+        for position in positions {
+            let event = &self.events[*position as usize];
+            // Duplicates share the start, so they can only be among the events with the same start
+            let duplicate = events
+                .iter()
+                .rev()
+                .take_while(|other| other.start == event.start)
+                .any(|other| *other == event);
+            if !duplicate {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
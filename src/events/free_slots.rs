@@ -0,0 +1,120 @@
+use std::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+
+use crate::events::haw_event::HawEventEntry;
+
+/// Gap between events.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+pub struct FreeSlot {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Length of the slot in minutes.
+    pub minutes: i64,
+}
+
+impl FreeSlot {
+    fn new(start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        FreeSlot {
+            start,
+            end,
+            minutes: (end - start).num_minutes(),
+        }
+    }
+}
+
+impl fmt::Display for FreeSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} - {} ({}h {:02}min)",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            self.minutes / 60,
+            self.minutes % 60
+        )
+    }
+}
+
+/// Returns the gaps between the events on each of the given days, limited to the time window
+/// `between` and at least `min` long.
+pub fn find_free_slots(
+    events: &[HawEventEntry],
+    days: &[NaiveDate],
+    between: (NaiveTime, NaiveTime),
+    min: Duration,
+) -> Vec<FreeSlot> {
+    let mut slots = vec![];
+
+    for day in days {
+        let window_start = day.and_time(between.0);
+        let window_end = day.and_time(between.1);
+
+        // Busy intervals clipped to the window, sorted by start
+        let mut busy: Vec<(NaiveDateTime, NaiveDateTime)> = events
+            .iter()
+            .filter(|event| event.start < window_end && event.end > window_start)
+            .map(|event| (event.start.max(window_start), event.end.min(window_end)))
+            .collect();
+        busy.sort();
+
+        let mut free_from = window_start;
+        for (start, end) in busy {
+            if start > free_from {
+                slots.push(FreeSlot::new(free_from, start));
+            }
+            free_from = free_from.max(end);
+        }
+        if window_end > free_from {
+            slots.push(FreeSlot::new(free_from, window_end));
+        }
+    }
+
+    slots.retain(|slot| slot.end - slot.start >= min);
+    slots
+}
+
+/// Parses a time window like "08:00-19:00".
+pub fn parse_time_window(window: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let error = || format!("Failed to parse time range '{}'. Expected format: HH:MM-HH:MM", window);
+
+    let (from, to) = window.split_once('-').ok_or_else(error)?;
+    let from = NaiveTime::parse_from_str(from.trim(), "%H:%M").map_err(|_| error())?;
+    let to = NaiveTime::parse_from_str(to.trim(), "%H:%M").map_err(|_| error())?;
+
+    if from >= to {
+        return Err(format!("Time range '{}' must end after it starts", window));
+    }
+
+    Ok((from, to))
+}
+
+/// Parses a duration like "45m", "2h", "1h30m" or plain minutes ("90").
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let error = || format!("Failed to parse duration '{}'. Expected format like 45m, 2h or 1h30m", duration);
+
+    if let Ok(minutes) = duration.parse::<i64>() {
+        return Ok(Duration::minutes(minutes));
+    }
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in duration.trim().chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' => {
+                let amount: i64 = number.parse().map_err(|_| error())?;
+                number.clear();
+                total += if c == 'h' { Duration::hours(amount) } else { Duration::minutes(amount) };
+            }
+            _ => return Err(error()),
+        }
+    }
+
+    if !number.is_empty() {
+        return Err(error());
+    }
+
+    Ok(total)
+}
@@ -1,23 +1,24 @@
 use std::{
-    error::Error,
     fmt,
-    fs::{self, File},
-    io::{Read, Write},
     path::{Path, PathBuf},
-    process::Command,
 };
 
-use chrono::{Datelike, NaiveDate, NaiveDateTime};
-use reqwest::blocking as reqwest;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
-use super::mup_scraper::MupLecture;
-
-use crate::{events::{event::*}, json_parser::Config};
-
-
-const DATA_URL: &str =
-    "https://raw.githubusercontent.com/HAWHHCalendarBot/eventfiles/refs/heads/main/"; // /faculty/event.json
+use super::changes;
+use super::error::EventError;
+use super::event_index::EventIndex;
+use super::overrides;
+use super::mup_scraper::{MupLecture, MUP_DEPARTMENT};
+
+use crate::{
+    cache::{self, Dataset, Freshness},
+    credentials,
+    events::event::*,
+    fetcher::{self, Backend, Fetched, Provider},
+    json_parser::Config,
+};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -33,113 +34,84 @@ impl Event for HawEventEntry {
     fn load_from_local(
         event: &EventMeta,
         cache_dir: PathBuf,
-    ) -> Result<Vec<Self>, Box<dyn Error>> {
-        // Read timestamp of local event data
-        let cache_dir_str = cache_dir
-            .to_str()
-            .ok_or("Cache directory path is not valid")?;
-        let mut file: File = File::open(format!("{}/eventdata/timestamp", &cache_dir_str))?;
-        let mut contents = String::new();
-
-        file.read_to_string(&mut contents)?;
-        let last_change = chrono::DateTime::from_timestamp(contents.parse()?, 0).unwrap();
-        let last_change_date = last_change.date_naive();
-
-        // If the data is older than 1 day, fetch new data
-        if chrono::Local::now()
-            .date_naive()
-            .signed_duration_since(last_change_date)
-            > chrono::Duration::days(1)
-        {
-            println!("Local event data is outdated. Fetching new data...");
-            Self::fetch_event_data(&cache_dir)?;
+    ) -> Result<Vec<Self>, EventError> {
+        // Refresh the local data if it is older than the configured TTL
+        match cache::freshness(Dataset::Events, &Dataset::Events.path(&cache_dir)) {
+            Freshness::Missing if fetcher::is_offline() => return Err(EventError::Offline),
+            Freshness::Missing => return Err(EventError::NotFound("Local event data".to_string())),
+            Freshness::Stale(age) if fetcher::is_offline() => cache::report_stale(Dataset::Events, age),
+            Freshness::Stale(_) => {
+                eprintln!("Local event data is outdated. Fetching new data...");
+                Self::fetch_event_data(&cache_dir)?;
+            }
+            Freshness::Fresh => {}
         }
 
-        // Load new data
-        let eventdata_path = Self::get_eventdata_dir(&cache_dir)?;
-
-        let path_str = format!("{}/{}.json", &event.department, &event.module);
-
-        let path = eventdata_path.join(Path::new(&path_str));
-
-        if !path.exists() {
-            return Err(format!(
-                "No data found for module '{}' in department '{}'",
+        // Load new data from the index
+        let index = EventIndex::load(&cache_dir)?;
+        if index.is_corrupt(event) {
+            return Err(EventError::CacheCorrupt(format!(
+                "data of module '{}' in department '{}' cannot be read",
                 &event.module, &event.department
-            )
-            .into());
+            )));
         }
 
-        // Read data
-        let file_content = fs::read_to_string(&path)?;
-
-        Ok(serde_json::from_str(&file_content)?)
+        match index.module_events(event) {
+            Some(events) => Ok(events.to_vec()),
+            None => Err(EventError::NotFound(format!(
+                "Data of module '{}' in department '{}'",
+                &event.module, &event.department
+            ))),
+        }
     }
 
-    fn get_all_events_for_date(
-        date: NaiveDate,
-    ) -> Result<Vec<Self>, Box<dyn Error>> {
-        let config = Config::load_config();
-        let event_descriptors = config.get_events();
-
-        if event_descriptors.is_none() {
-            return Err("No event descriptors found in config".into());
-        }
+    fn get_start(&self) -> NaiveDateTime {
+        self.start
+    }
 
-        let event_descriptors = event_descriptors.unwrap();
+    fn get_configured_modules() -> Result<Vec<EventMeta>, EventError> {
+        EventMeta::from_config()
+    }
 
-        let mut events: Vec<Self> = vec![];
-        for meta in event_descriptors.iter() {
-            // Split format "department:module"
-            let (department, module) = meta.split_once(':').ok_or("Invalid event descriptor format")?;
+    fn get_events_for_module(event: &EventMeta) -> Result<Vec<Self>, EventError> {
+        let events = Self::get_raw_events_for_module(event)?;
+        let event_overrides = overrides::from_entries(&Config::load_config().get_event_overrides());
 
-            // Build event_meta
-            let event_meta = EventMeta {
-                department: department.to_string(),
-                module: module.to_string(),
-            };
+        Ok(overrides::apply(event, events, &event_overrides))
+    }
 
-            let module_events = Self::get_events_for_module(&event_meta)?;
-            events.extend(module_events);
-        }
+    fn get_all_events_for_date(
+        date: NaiveDate,
+    ) -> Result<Vec<Self>, EventError> {
+        let events = Self::get_events_in_range(date, date)?;
 
         if events.is_empty() {
-            return Err(format!("No events found for date {}", date).into());
+            return Err(EventError::NoDataForDate { date, modules: vec![] });
         }
 
-        // Filter events for the specified date
-        events.retain(|event| event.start.date() == date);
-
         Ok(events)
     }
 
     fn get_events_for_date(
         event_descriptor: Vec<EventMeta>,
         date: NaiveDate,
-    ) -> Result<Vec<Self>, Box<dyn Error>> {
-        let mut events: Vec<Self> = vec![];
-        for module in event_descriptor.iter() {
-            let module_events = Self::get_events_for_module(module)?;
-
-            for event in module_events.iter() {
-                if date.day() == event.start.day() {
-                    events.push(event.clone());
-                }
-            }
-        }
+    ) -> Result<Vec<Self>, EventError> {
+        let events = Self::get_module_events_in_range(&event_descriptor, date, date)?;
 
         if events.is_empty() {
-            return Err(format!(
-                "No events found for date {} in modules: {:?}",
-                date, event_descriptor
-            )
-            .into());
+            return Err(EventError::NoDataForDate {
+                date,
+                modules: event_descriptor
+                    .iter()
+                    .map(|meta| format!("{}:{}", meta.department, meta.module))
+                    .collect(),
+            });
         }
 
         Ok(events)
     }
 
-    fn fetch_events_for_module(event: &EventMeta, department: &str) -> Result<Vec<Self>, Box<dyn Error>> {
+    fn fetch_events_for_module(event: &EventMeta, department: &str) -> Result<Vec<Self>, EventError> {
         if event.department.is_empty() || event.module.is_empty() {
             return Err(format!(
                 "Department and module must be specified. Got: department='{}', module='{}'",
@@ -151,162 +123,157 @@ impl Event for HawEventEntry {
         let valid_modules = Self::get_modules_for_department(department, None)?;
 
         if !valid_modules.contains(&event.module) {
-            return Err(format!(
-                "Invalid module '{}' for department '{}'",
-                event.module, department
-            ).into());
+            return Err(EventError::InvalidModule {
+                department: department.to_string(),
+                module: event.module.clone(),
+            });
         }
 
-        let url = format!("{DATA_URL}/{}/{}.json", &event.department, &event.module);
-
-        let result = reqwest::get(url)?;
+        let path = format!("{}/{}.json", &event.department, &event.module);
 
-        // Handle HTTP errors
-        if result.status().is_client_error() || result.status().is_server_error() {
-            return Err(result.status().to_string().into());
+        match Provider::Events.source().fetch_file(&path)? {
+            Some(content) => Ok(serde_json::from_str(&content)?),
+            None => Err(EventError::NotFound(format!(
+                "Module '{}' in department '{}'",
+                event.module, event.department
+            ))),
         }
-
-        Ok(serde_json::from_str(&result.text()?)?)
     }
 
     /// Fetches Eventdata and stores it in the cache dir
-    /// Fetch Event data from git repo (https://github.com/HAWHHCalendarBot/eventfiles.git) and save it locally
-    fn fetch_event_data(cache_dir: &PathBuf) -> Result<(), Box<dyn Error>> {
+    /// Fetch Event data from the configured source (default https://github.com/HAWHHCalendarBot/eventfiles) and save it locally
+    fn fetch_event_data(cache_dir: &PathBuf) -> Result<(), EventError> {
         // Check locally if the data is available
         let eventdata_path = Self::get_eventdata_dir(&cache_dir)?;
 
-        // Create the event data directory if it doesn't exist
-        if !eventdata_path.exists() {
-            fs::create_dir_all(&eventdata_path)?;
-        } else {
-            // If the directory already exists, remove it to ensure a fresh clone
-            fs::remove_dir_all(&eventdata_path)?;
-            fs::create_dir_all(&eventdata_path)?;
-        }
-
-        println!("fetching into: {:?}", &eventdata_path);
+        // Keep the configured modules to detect schedule changes after the refresh
+        let previous = changes::read_modules(&eventdata_path, &EventMeta::from_config().unwrap_or_default());
 
-        // Clone the event data repository
-        let output = Command::new("git")
-            .arg("clone")
-            .arg("--depth")
-            .arg("1")
-            .arg("https://github.com/HAWHHCalendarBot/eventfiles.git")
-            .arg(&eventdata_path)
-            .output()?;
+        eprintln!("fetching into: {:?}", &eventdata_path);
 
-        if !output.status.success() {
-            let error_message = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Failed to clone event data: {}", error_message).into());
-        }
+        // Download into a staging directory, the current data stays usable if anything fails
+        cache::refresh(&eventdata_path, |staging| {
+            // Download the event data repository
+            let source = Provider::Events.source();
+            if fetcher::fetch(&source, Backend::from_config(), &eventdata_path, staging)? == Fetched::NotModified {
+                eprintln!("Event data is unchanged.");
+            }
 
-        //fetching Event Data that are not in repo (MuP):
-        let conf = Config::load_config();
-        match conf.get_username() {
-            Some(user) => {
-                match conf.get_password() {
-                    Some(password) => {
-                        match  MupLecture::fetch_all_mup_plans_to_cache(user, password){
-                            Ok(_) => {println!("Mup Lectures fetched!")},
-                            Err(e) => {println!("Cant fetch Mup Lectures, cause: {}", e)}
+            //fetching Event Data that are not in repo (MuP):
+            let conf = Config::load_config();
+            let fetched = match (conf.get_username(), fetcher::is_offline()) {
+                (_, true) => {
+                    eprintln!("Offline mode, keeping the previous MuP Lectures");
+                    false
+                }
+                (Some(user), false) => match credentials::password(&conf) {
+                    Ok(Some(password)) => match MupLecture::fetch_all_mup_plans_to_cache(user, password, &eventdata_path, staging) {
+                        Ok(_) => {
+                            eprintln!("Mup Lectures fetched!");
+                            true
+                        }
+                        Err(e) => {
+                            eprintln!("Cant fetch Mup Lectures, cause: {}", e);
+                            false
                         }
                     },
-                    None => {println!("found no Password, cant fetch MuP Lectures")},
-                    
-                };
+                    Ok(None) => {
+                        eprintln!("found no Password, cant fetch MuP Lectures");
+                        false
+                    }
+                    Err(e) => {
+                        eprintln!("Cant read the MuP Password, cause: {}", e);
+                        false
+                    }
+                },
+                (None, false) => {
+                    eprintln!("found no Username, cant fetch MuP Lectures");
+                    false
+                }
+            };
+
+            // Keep the last scraped MuP lectures instead of dropping them
+            let previous_mup = eventdata_path.join(MUP_DEPARTMENT);
+            if !fetched && previous_mup.exists() {
+                cache::copy_dir(&previous_mup, &staging.join(MUP_DEPARTMENT))?;
+            }
 
-            },
-            None => {println!("found no Username, cant fetch MuP Lectures")}  
-        };
+            cache::write_timestamp(staging)?;
 
-        
+            Ok(())
+        }, Self::validate_event_data)?;
 
         // If the clone was successful, return Ok
-        println!("Event data cloned successfully.");
+        eprintln!("Event data cloned successfully.");
 
-        // Refresh Timestamp
-        let mut file = File::create(Path::join(&eventdata_path, "./timestamp"))?;
-        file.write_all(&chrono::Local::now().timestamp().to_string().into_bytes())
-            .expect("couldnt write timestamp");
+        // Index the new data for queries across all modules
+        EventIndex::rebuild(cache_dir)?;
+
+        changes::update_snapshots(cache_dir, &eventdata_path, previous)?;
 
         Ok(())
     }
     
-    fn get_modules_for_department(department: &str, filter: Option<&str>) -> Result<Vec<String>, Box<dyn Error>> {
+    fn get_modules_for_department(department: &str, filter: Option<&str>) -> Result<Vec<String>, EventError> {
         let cache_dir = Self::get_cache_dir()?;
         let eventdata_path = Self::get_eventdata_dir(&cache_dir)?;
         let department_path = eventdata_path.join(department);
 
         // If event data directory doesn't exist at all, fetch it
         if !eventdata_path.exists() {
-            println!("Event data directory does not exist. Fetching event data...");
+            eprintln!("Event data directory does not exist. Fetching event data...");
             Self::fetch_event_data(&cache_dir)?;
-            println!("");
+            eprintln!();
         }
 
         // If specific department directory doesn't exist, also fetch event data
         else if !department_path.exists() {
-            println!("Department '{}' directory does not exist locally. Fetching event data...", department);
+            eprintln!("Department '{}' directory does not exist locally. Fetching event data...", department);
             Self::fetch_event_data(&cache_dir)?;
-            println!("");
+            eprintln!();
         }
 
-        let mut modules = vec![];
-        for entry in fs::read_dir(department_path)? {
-            let entry = entry?;
-            if entry.file_type()?.is_file() {
-                if let Some(module) = entry.file_name().to_str() {
-                    if module.ends_with(".json") && !module.starts_with('.') {
-                        modules.push(module.trim_end_matches(".json").to_string());
-                    }
-                }
-            }
-        }
+        let mut modules = EventIndex::load(&cache_dir)?.modules(department);
 
         if let Some(filter) = filter {
             modules.retain(|module| module.contains(filter));
         }
 
         if modules.is_empty() {
-            return Err(format!("No modules found for department '{}'", department).into());
+            return Err(EventError::NotFound(format!("Modules of department '{}'", department)));
         }
 
         Ok(modules)
     }
     
-    fn get_departments() -> Result<Vec<String>, Box<dyn Error>> {
+    fn get_departments() -> Result<Vec<String>, EventError> {
         let cache_dir = Self::get_cache_dir()?;
         let eventdata_path = Self::get_eventdata_dir(&cache_dir)?;
 
         if !eventdata_path.exists() {
-            return Err("Event data directory does not exist".into());
+            return Err(EventError::NotFound("Event data directory".to_string()));
         }
 
-        let mut departments = vec![];
-        for entry in fs::read_dir(eventdata_path)? {
-            let entry = entry?;
-            if entry.file_type()?.is_file() {
-                continue; // Skip files
-            }
-
-            
-            if let Some(department) = entry.file_name().to_str() {
-                if !department.starts_with('.') {
-                    departments.push(department.to_string());
-                }
-            }
-
-             
-        }
+        let departments = EventIndex::load(&cache_dir)?.departments();
 
         if departments.is_empty() {
-            return Err("No departments found".into());
+            return Err(EventError::NotFound("Departments".to_string()));
         }
 
         Ok(departments)
     }
 }
 
+impl HawEventEntry {
+    /// Checks that downloaded event data contains module files before it replaces the current data.
+    pub fn validate_event_data(eventdata_path: &Path) -> Result<(), EventError> {
+        if !cache::contains_file_with_extension(eventdata_path, "json")? {
+            return Err(EventError::CacheCorrupt("downloaded event data contains no modules".to_string()));
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for HawEventEntry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -0,0 +1,542 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{error::EventError, event::*, haw_event::HawEventEntry},
+    json_parser::Config,
+};
+
+/// Department name under which all configured ICS files are listed.
+pub const ICS_DEPARTMENT: &str = "ics";
+
+/// Upper bound for open-ended recurrence rules (no COUNT / UNTIL).
+const MAX_RECURRENCE_YEARS: u32 = 10;
+
+/// Event parsed from a local iCalendar (.ics) file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsEvent {
+    pub name: String,
+    pub location: String,
+    pub description: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl IcsEvent {
+    /// Parses all VEVENTs of an ics file and expands their recurrence rules.
+    pub fn parse_file(path: &Path) -> Result<Vec<Self>, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read ICS file '{}': {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// Parses all VEVENTs of an iCalendar document and expands their recurrence rules.
+    pub fn parse(content: &str) -> Result<Vec<Self>, Box<dyn Error>> {
+        let vevents = parse_vevents(content)?;
+
+        // Occurrences that are replaced by a modified instance (RECURRENCE-ID)
+        let overridden: HashSet<(String, NaiveDateTime)> = vevents
+            .iter()
+            .filter_map(|v| Some((v.uid.clone(), v.recurrence_id?)))
+            .collect();
+
+        let mut events = Vec::new();
+        for vevent in &vevents {
+            if let Some(reason) = &vevent.unsupported_rrule {
+                eprintln!("Warning: skipping ICS event '{}': {}", vevent.summary, reason);
+                continue;
+            }
+
+            let duration = vevent.end - vevent.start;
+            let starts = match (&vevent.rrule, vevent.recurrence_id) {
+                (Some(rrule), None) => rrule.expand(vevent.start)?,
+                _ => vec![vevent.start],
+            };
+
+            for start in starts {
+                if vevent.exdates.contains(&start) {
+                    continue;
+                }
+                if vevent.recurrence_id.is_none() && overridden.contains(&(vevent.uid.clone(), start)) {
+                    continue;
+                }
+
+                events.push(IcsEvent {
+                    name: vevent.summary.clone(),
+                    location: vevent.location.clone(),
+                    description: vevent.description.clone(),
+                    start,
+                    end: start + duration,
+                });
+            }
+        }
+
+        events.sort_by_key(|event| event.start);
+        Ok(events)
+    }
+
+    /// Returns the file path of a configured ics source.
+    fn get_source_path(name: &str) -> Result<PathBuf, EventError> {
+        Config::load_config()
+            .get_ics_sources()
+            .into_iter()
+            .find(|(source, _)| source == name)
+            .map(|(_, path)| PathBuf::from(path))
+            .ok_or_else(|| EventError::NotFound(format!("ICS source '{}'", name)))
+    }
+}
+
+impl Event for IcsEvent {
+    fn get_start(&self) -> NaiveDateTime {
+        self.start
+    }
+
+    fn get_configured_modules() -> Result<Vec<EventMeta>, EventError> {
+        Ok(Config::load_config()
+            .get_ics_sources()
+            .into_iter()
+            .map(|(name, _)| EventMeta {
+                department: ICS_DEPARTMENT.to_string(),
+                module: name,
+            })
+            .collect())
+    }
+
+    fn get_all_events_for_date(date: NaiveDate) -> Result<Vec<Self>, EventError> {
+        Self::get_events_in_range(date, date)
+    }
+
+    fn get_events_for_date(event_descriptor: Vec<EventMeta>, date: NaiveDate) -> Result<Vec<Self>, EventError> {
+        let events = Self::get_module_events_in_range(&event_descriptor, date, date)?;
+
+        if events.is_empty() {
+            return Err(EventError::NoDataForDate {
+                date,
+                modules: event_descriptor
+                    .iter()
+                    .map(|meta| format!("{}:{}", meta.department, meta.module))
+                    .collect(),
+            });
+        }
+
+        Ok(events)
+    }
+
+    fn get_modules_for_department(department: &str, filter: Option<&str>) -> Result<Vec<String>, EventError> {
+        if department != ICS_DEPARTMENT {
+            return Err(EventError::NotFound(format!("Modules of department '{}'", department)));
+        }
+
+        let mut modules: Vec<String> = Config::load_config()
+            .get_ics_sources()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        if let Some(filter) = filter {
+            modules.retain(|module| module.contains(filter));
+        }
+
+        if modules.is_empty() {
+            return Err(EventError::NotFound("ICS sources".to_string()));
+        }
+
+        Ok(modules)
+    }
+
+    fn get_departments() -> Result<Vec<String>, EventError> {
+        Ok(vec![ICS_DEPARTMENT.to_string()])
+    }
+
+    /// ICS sources are local files, the cache directory is not used.
+    fn load_from_local(event: &EventMeta, _cache_dir: PathBuf) -> Result<Vec<Self>, EventError> {
+        let path = Self::get_source_path(&event.module)?;
+        Ok(Self::parse_file(&path)?)
+    }
+
+    fn fetch_events_for_module(event: &EventMeta, department: &str) -> Result<Vec<Self>, EventError> {
+        if department != ICS_DEPARTMENT {
+            return Err(EventError::InvalidModule {
+                department: department.to_string(),
+                module: event.module.clone(),
+            });
+        }
+
+        let path = Self::get_source_path(&event.module)?;
+        Ok(Self::parse_file(&path)?)
+    }
+
+    /// Nothing to fetch, ICS sources are read directly from disk.
+    fn fetch_event_data(_cache_dir: &PathBuf) -> Result<(), EventError> {
+        Ok(())
+    }
+}
+
+impl From<IcsEvent> for HawEventEntry {
+    fn from(event: IcsEvent) -> Self {
+        HawEventEntry {
+            name: event.name,
+            location: event.location,
+            description: event.description,
+            start: event.start,
+            end: event.end,
+        }
+    }
+}
+
+impl fmt::Display for IcsEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\nLocation: {}\nDescription: {}\nStart: {}\nEnd: {}",
+            self.name, self.location, self.description, self.start, self.end
+        )
+    }
+}
+
+/*########################################
+ICS Parser
+########################################*/
+
+/// A single VEVENT block before recurrence expansion.
+#[derive(Debug, Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    location: String,
+    description: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    rrule: Option<RRule>,
+    /// Reason why the recurrence rule cannot be expanded, the event is skipped.
+    unsupported_rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Supported subset of RFC 5545 recurrence rules.
+#[derive(Debug)]
+struct RRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    by_day: Vec<Weekday>,
+}
+
+impl RRule {
+    fn parse(value: &str) -> Result<Self, Box<dyn Error>> {
+        let mut frequency = None;
+        let mut rrule = RRule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+        };
+
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("Invalid RRULE part '{}'", part))?;
+            match key {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported RRULE frequency '{}'", value).into()),
+                    })
+                }
+                "INTERVAL" => rrule.interval = value.parse()?,
+                "COUNT" => rrule.count = Some(value.parse()?),
+                "UNTIL" => rrule.until = Some(parse_datetime(value)?),
+                "BYDAY" => {
+                    rrule.by_day = value
+                        .split(',')
+                        .map(parse_weekday)
+                        .collect::<Result<Vec<Weekday>, Box<dyn Error>>>()?
+                }
+                // Weeks are expanded from monday, the default week start
+                "WKST" if value == "MO" => {}
+                // BYMONTHDAY, BYSETPOS, BYMONTH, ... would select other dates than the expansion
+                _ => return Err(format!("Unsupported RRULE part '{}'", part).into()),
+            }
+        }
+
+        rrule.frequency = frequency.ok_or("RRULE without FREQ")?;
+        if !rrule.by_day.is_empty() && rrule.frequency != Frequency::Weekly {
+            return Err("RRULE BYDAY is only supported for weekly rules".into());
+        }
+        if rrule.interval == 0 {
+            return Err("RRULE INTERVAL must be greater than 0".into());
+        }
+
+        Ok(rrule)
+    }
+
+    /// Returns all start times of the recurrence set, beginning with `start`.
+    fn expand(&self, start: NaiveDateTime) -> Result<Vec<NaiveDateTime>, Box<dyn Error>> {
+        let horizon = start
+            .checked_add_months(Months::new(12 * MAX_RECURRENCE_YEARS))
+            .ok_or("Recurrence out of range")?;
+        let until = self.until.map_or(horizon, |until| until.min(horizon));
+
+        let mut starts = vec![];
+        let mut period = 0u32;
+
+        loop {
+            let period_start = match self.frequency {
+                Frequency::Daily => Some(start + Duration::days(period.into())),
+                Frequency::Weekly => Some(start + Duration::weeks(period.into())),
+                Frequency::Monthly => start.checked_add_months(Months::new(period)),
+                Frequency::Yearly => start.checked_add_months(Months::new(12 * period)),
+            };
+            if period_start.is_none_or(|period_start| period_start > until) {
+                break;
+            }
+
+            for candidate in self.period_candidates(start, period) {
+                if candidate < start || candidate > until {
+                    continue;
+                }
+                if self.count.is_some_and(|count| starts.len() >= count) {
+                    return Ok(starts);
+                }
+                starts.push(candidate);
+            }
+
+            period += self.interval;
+        }
+
+        Ok(starts)
+    }
+
+    /// Returns the occurrences within the n-th period after `start`.
+    fn period_candidates(&self, start: NaiveDateTime, period: u32) -> Vec<NaiveDateTime> {
+        match self.frequency {
+            Frequency::Daily => vec![start + Duration::days(period.into())],
+            Frequency::Weekly => {
+                let week_start = start + Duration::weeks(period.into());
+                if self.by_day.is_empty() {
+                    return vec![week_start];
+                }
+
+                let monday = week_start - Duration::days(week_start.weekday().num_days_from_monday().into());
+                let mut days: Vec<NaiveDateTime> = self
+                    .by_day
+                    .iter()
+                    .map(|day| monday + Duration::days(day.num_days_from_monday().into()))
+                    .collect();
+                days.sort();
+                days
+            }
+            // Months without the start day (e.g. the 31st) are skipped as defined by RFC 5545
+            Frequency::Monthly => start
+                .checked_add_months(Months::new(period))
+                .filter(|date| date.day() == start.day())
+                .into_iter()
+                .collect(),
+            Frequency::Yearly => start
+                .checked_add_months(Months::new(12 * period))
+                .filter(|date| date.day() == start.day())
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Splits an iCalendar document into VEVENTs.
+fn parse_vevents(content: &str) -> Result<Vec<VEvent>, Box<dyn Error>> {
+    let mut vevents = vec![];
+    let mut current: Option<VEvent> = None;
+    let mut end: Option<NaiveDateTime> = None;
+    let mut duration: Option<Duration> = None;
+    let mut has_start = false;
+    let mut all_day = false;
+    // Nested components like VALARM must not overwrite event properties
+    let mut nested_depth = 0;
+
+    for line in unfold_lines(content) {
+        let Some((name_and_params, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = name_and_params.split(';');
+        let name = params.next().unwrap_or_default().to_uppercase();
+        let is_date = params.any(|param| param == "VALUE=DATE");
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => {
+                current = Some(VEvent::default());
+                end = None;
+                duration = None;
+                has_start = false;
+                all_day = false;
+            }
+            ("BEGIN", Some(_)) => nested_depth += 1,
+            ("END", Some(_)) if nested_depth > 0 => nested_depth -= 1,
+            ("END", Some(_)) if value == "VEVENT" => {
+                let mut vevent = current.take().unwrap_or_default();
+                if !has_start {
+                    return Err(format!("VEVENT '{}' has no DTSTART", vevent.summary).into());
+                }
+
+                vevent.end = match (end, duration) {
+                    (Some(end), _) => end,
+                    (None, Some(duration)) => vevent.start + duration,
+                    (None, None) if all_day => vevent.start + Duration::days(1),
+                    (None, None) => vevent.start,
+                };
+                vevents.push(vevent);
+            }
+            (_, Some(_)) if nested_depth > 0 => {}
+            ("UID", Some(vevent)) => vevent.uid = value.to_string(),
+            ("SUMMARY", Some(vevent)) => vevent.summary = unescape_text(value),
+            ("LOCATION", Some(vevent)) => vevent.location = unescape_text(value),
+            ("DESCRIPTION", Some(vevent)) => vevent.description = unescape_text(value),
+            ("DTSTART", Some(vevent)) => {
+                vevent.start = parse_datetime(value)?;
+                has_start = true;
+                all_day = is_date || value.len() == 8;
+            }
+            ("DTEND", Some(_)) => end = Some(parse_datetime(value)?),
+            ("DURATION", Some(_)) => duration = Some(parse_duration(value)?),
+            ("RRULE", Some(vevent)) => match RRule::parse(value) {
+                Ok(rrule) => vevent.rrule = Some(rrule),
+                Err(e) => vevent.unsupported_rrule = Some(format!("{} in '{}'", e, value)),
+            },
+            ("EXDATE", Some(vevent)) => {
+                for exdate in value.split(',') {
+                    vevent.exdates.push(parse_datetime(exdate)?);
+                }
+            }
+            ("RECURRENCE-ID", Some(vevent)) => {
+                vevent.recurrence_id = Some(parse_datetime(value)?)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(vevents)
+}
+
+/// Joins folded content lines (RFC 5545, section 3.1).
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+            }
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+/// Parses DATE and DATE-TIME values. UTC times are converted to local time,
+/// floating times and times with a TZID are taken as they are.
+fn parse_datetime(value: &str) -> Result<NaiveDateTime, Box<dyn Error>> {
+    let value = value.trim();
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let datetime = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")?;
+        let utc: DateTime<Utc> = DateTime::from_naive_utc_and_offset(datetime, Utc);
+        return Ok(utc.with_timezone(&Local).naive_local());
+    }
+
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d")?;
+        return Ok(date.and_time(NaiveTime::MIN));
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map_err(|e| format!("Invalid ICS date '{}': {}", value, e).into())
+}
+
+/// Parses DURATION values like "PT1H30M" or "P1D".
+fn parse_duration(value: &str) -> Result<Duration, Box<dyn Error>> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P').ok_or_else(|| format!("Invalid ICS duration '{}'", value))?;
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse()?;
+                number.clear();
+                duration += match unit {
+                    'W' => Duration::weeks(amount),
+                    'D' => Duration::days(amount),
+                    'H' => Duration::hours(amount),
+                    'M' => Duration::minutes(amount),
+                    'S' => Duration::seconds(amount),
+                    _ => return Err(format!("Invalid ICS duration unit '{}'", unit).into()),
+                };
+            }
+        }
+    }
+
+    Ok(if negative { -duration } else { duration })
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, Box<dyn Error>> {
+    // Ordinals like "2TU" select a single day of the month or year, which is not expanded
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Unsupported weekday '{}'", value).into()),
+    }
+}
+
+/// Reverts TEXT escaping (RFC 5545, section 3.3.11).
+fn unescape_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+
+    text
+}
@@ -0,0 +1,162 @@
+use std::{error::Error, fs, path::Path};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+use crate::events::{event::EventMeta, haw_event::HawEventEntry, schedule};
+
+const PRODID: &str = concat!("-//HAWHHCalendarBot//", env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"), "//DE");
+const TIMEZONE: &str = "Europe/Berlin";
+
+/// Static VTIMEZONE definition for Europe/Berlin (CET/CEST), the timezone of all HAW event data.
+const VTIMEZONE: [&str; 17] = [
+    "BEGIN:VTIMEZONE",
+    "TZID:Europe/Berlin",
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:+0100",
+    "TZOFFSETTO:+0200",
+    "TZNAME:CEST",
+    "DTSTART:19700329T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:+0200",
+    "TZOFFSETTO:+0100",
+    "TZNAME:CET",
+    "DTSTART:19701025T030000",
+    "RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU",
+    "END:STANDARD",
+    "END:VTIMEZONE",
+];
+
+/// Builds an RFC 5545 VCALENDAR from the given module events.
+/// Events are sorted by start time and module, and every property is derived from the
+/// event data only, so exporting the same data twice yields an identical file.
+pub fn to_ics(events: &[(EventMeta, HawEventEntry)]) -> String {
+    let mut sorted: Vec<&(EventMeta, HawEventEntry)> = events.iter().collect();
+    sorted.sort_by(|(meta_a, a), (meta_b, b)| {
+        (a.start, &meta_a.department, &meta_a.module, &a.name)
+            .cmp(&(b.start, &meta_b.department, &meta_b.module, &b.name))
+    });
+    sorted.dedup_by(|(meta_a, a), (meta_b, b)| uid(meta_a, a) == uid(meta_b, b));
+
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-TIMEZONE:{}", TIMEZONE),
+    ];
+    lines.extend(VTIMEZONE.iter().map(|line| line.to_string()));
+
+    for (meta, event) in sorted {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", uid(meta, event)));
+        // DTSTAMP is the start in UTC instead of the export time to keep re-exports diff-friendly
+        lines.push(format!("DTSTAMP:{}Z", format_datetime(to_utc(event.start))));
+        lines.push(format!("DTSTART;TZID={}:{}", TIMEZONE, format_datetime(event.start)));
+        lines.push(format!("DTEND;TZID={}:{}", TIMEZONE, format_datetime(event.end)));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.name)));
+        if !event.location.is_empty() {
+            lines.push(format!("LOCATION:{}", escape_text(&event.location)));
+        }
+        if !event.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&event.description)));
+        }
+        lines.push(format!("CATEGORIES:{}", escape_text(&meta.module)));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// Builds the calendar of all modules configured in the config.
+/// Returns the calendar together with the number of exported events.
+pub fn configured_calendar() -> Result<(String, usize), Box<dyn Error>> {
+    let events: Vec<(EventMeta, HawEventEntry)> = schedule::load_module_events(&EventMeta::from_config()?)?
+        .into_iter()
+        .flat_map(|(event_meta, events)| events.into_iter().map(move |event| (event_meta.clone(), event)))
+        .collect();
+
+    Ok((to_ics(&events), events.len()))
+}
+
+/// Writes the calendar to the given path.
+pub fn write_ics(calendar: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() && !parent.as_os_str().is_empty() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, calendar)?;
+    Ok(())
+}
+
+/// Stable identifier for an event occurrence, based on module and start time.
+pub fn uid(meta: &EventMeta, event: &HawEventEntry) -> String {
+    let name: String = event
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+
+    format!(
+        "{}-{}-{}-{}@{}",
+        format_datetime(event.start),
+        meta.department,
+        meta.module,
+        name,
+        env!("CARGO_PKG_NAME")
+    )
+}
+
+fn format_datetime(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Converts a local time in Europe/Berlin to UTC, following the rules of `VTIMEZONE`.
+fn to_utc(local: NaiveDateTime) -> NaiveDateTime {
+    let last_sunday = |month: u32| {
+        let last_day = NaiveDate::from_ymd_opt(local.year(), month + 1, 1)
+            .and_then(|first| first.pred_opt())
+            .expect("March and October have a following month");
+        last_day - Duration::days(last_day.weekday().num_days_from_sunday().into())
+    };
+    let summer_start = last_sunday(3).and_hms_opt(2, 0, 0).expect("valid time");
+    let summer_end = last_sunday(10).and_hms_opt(3, 0, 0).expect("valid time");
+
+    let offset = if (summer_start..summer_end).contains(&local) { 2 } else { 1 };
+    local - Duration::hours(offset)
+}
+
+/// Escapes TEXT values (RFC 5545, section 3.3.11).
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line after 75 octets and terminates it with CRLF (RFC 5545, section 3.1).
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
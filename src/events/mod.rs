@@ -1,5 +1,18 @@
+pub(crate) mod changes;
+pub(crate) mod conflicts;
+pub(crate) mod error;
 pub(crate) mod event;
+pub(crate) mod event_index;
+pub(crate) mod free_slots;
 pub(crate) mod haw_event;
+pub(crate) mod ics_event;
+pub(crate) mod ics_export;
 pub(crate) mod mup_scraper;
+pub(crate) mod overrides;
+pub(crate) mod planner;
+pub(crate) mod rooms;
+pub(crate) mod schedule;
+pub(crate) mod search;
+pub(crate) mod upcoming;
 #[cfg(test)]
 mod tests;
\ No newline at end of file
@@ -1,17 +1,61 @@
-use chrono::{Datelike, Local};
+use chrono::Local;
 use clap::builder::Str;
 use reqwest::blocking::Client;
+use reqwest::StatusCode;
 use std::time::Duration;
-use std::{error::Error};
+use std::{error::Error, fmt};
 use scraper::{Html, Selector};
 use regex::Regex;
 use serde::{Serialize, Deserialize};
-use std::io::BufReader;
 use std::io::BufWriter;
 use std::fs::File;
 use std::fs;
-use chrono::Duration as ChronoDuration;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime,  Weekday};
+use std::path::Path;
+use chrono::{NaiveDateTime, NaiveTime,  Weekday};
+use crate::events::{error::EventError, haw_event::HawEventEntry};
+use crate::json_parser::Config;
+use crate::semester::{Semester, WeekParity};
+
+/// Department the scraped MuP lectures are stored under in the event data directory.
+pub const MUP_DEPARTMENT: &str = "maschienenbau-und-produktion";
+
+/// Index page linking the plans of all semester groups.
+const MUP_PLAN_INDEX: &str = "https://www.mp.haw-hamburg.de/auth/vorlesungsplan/";
+
+/// File within the cache directory storing the discovered semester groups.
+const GROUPS_FILE: &str = "mupgroups.json";
+
+/// Semester group with a plan page, e.g. `B_MT1` linked as "Mechatronik 1. Semester".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MupGroup {
+    /// File name of the plan page without `.php`
+    pub id: String,
+    /// Link text on the index page
+    pub name: String,
+    pub url: String,
+}
+
+impl MupGroup {
+    /// Returns the module the lectures of the group are stored as, e.g. `b-mt1`.
+    pub fn module(&self) -> String {
+        MupLecture::module_name(&self.id)
+    }
+
+    /// Reads the groups found by the last discovery, `None` if there was none yet.
+    pub fn load_all(cache_dir: &Path) -> Option<Vec<MupGroup>> {
+        let content = fs::read_to_string(cache_dir.join(GROUPS_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save_all(groups: &[MupGroup], cache_dir: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(cache_dir)?;
+        fs::write(cache_dir.join(GROUPS_FILE), serde_json::to_string_pretty(groups)?)
+    }
+}
+
+/// Name of a semester group (e.g. `B_MT1`) and its scraped lectures.
+pub type GroupPlan = (String, Vec<MupLecture>);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MupLecture {
     name: String,
@@ -21,23 +65,103 @@ pub struct MupLecture {
     end: NaiveDateTime,
 }
 
+/// Timetable cell that was parsed into a weekly lecture.
+#[derive(Debug, Serialize)]
+pub struct ParsedCell {
+    pub row: usize,
+    pub column: usize,
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub name: String,
+    pub location: String,
+    pub description: String,
+    pub parity: WeekParity,
+}
+
+impl fmt::Display for ParsedCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {}, column {}: {} {}-{} {} in {}",
+            self.row,
+            self.column,
+            self.weekday,
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            self.name,
+            self.location
+        )?;
+        match self.parity {
+            WeekParity::Every => Ok(()),
+            WeekParity::A => write!(f, " (A weeks)"),
+            WeekParity::B => write!(f, " (B weeks)"),
+        }
+    }
+}
+
+/// Timetable cell that could not be parsed.
+#[derive(Debug, Serialize)]
+pub struct ParseDiagnostic {
+    pub row: usize,
+    pub column: usize,
+    pub content: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.content.is_empty() {
+            write!(f, "row {}, column {}: {}", self.row, self.column, self.reason)
+        } else {
+            write!(f, "row {}, column {}: {} in '{}'", self.row, self.column, self.reason, self.content)
+        }
+    }
+}
+
+/// Result of parsing a plan page.
+#[derive(Debug, Default, Serialize)]
+pub struct ParseReport {
+    pub cells: Vec<ParsedCell>,
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl ParseReport {
+    /// Repeats every parsed cell weekly over the lecture period of the semester.
+    pub fn lectures(&self, semester_group: &str, semester: &Semester) -> Vec<MupLecture> {
+        let mut lectures = Vec::new();
+
+        for cell in &self.cells {
+            // Every weekly occurrence within the lecture period, A/B weeks only every other week
+            for date in semester.lecture_dates(cell.weekday, cell.parity) {
+                lectures.push(MupLecture {
+                    name: format!("{}-{}", semester_group, cell.name),
+                    location: cell.location.clone(),
+                    description: cell.description.clone(),
+                    start: date.and_time(cell.start),
+                    end: date.and_time(cell.end),
+                });
+            }
+        }
+
+        lectures
+    }
+}
+
 impl MupLecture {
     /*########################################
     Web Scraper:
     ########################################*/
 
-    fn generate_urls(base_url: &str, custom_ending: &str, ending: &str, iter: i8) -> Vec<String> {
-        (1..iter)
-            .map(|i| format!("{}{}{}{}", base_url, custom_ending, i, ending))
-            .collect()
-    }
-
     pub fn scrape_lecture_plan(user: String, password: String, url: String, semester_groupe: String) -> Result<Vec<MupLecture>, Box<dyn Error>> {
         let body = Self::fetch_html(user, password, &url)?;
-        let lecture_table = Self::extract_lecture_table(&body)?;
-        let lecture_structs = Self::parse_lecture_table(lecture_table, semester_groupe);
+        let report = Self::parse_html(&body);
+        for diagnostic in &report.diagnostics {
+            eprintln!("Warning: {}: {}", semester_groupe, diagnostic);
+        }
 
-        Ok(lecture_structs)
+        let semester = Semester::upcoming(Local::now().date_naive());
+        Ok(report.lectures(&semester_groupe, &semester))
     }
 
     fn fetch_html(user: String, password: String, url: &str) -> Result<String, Box<dyn Error>> {
@@ -50,6 +174,9 @@ impl MupLecture {
             .basic_auth(user, Some(password))
             .send()?;
 
+        if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            return Err(EventError::AuthFailed(response.status().to_string()).into());
+        }
         if !response.status().is_success() {
             return Err(format!("Seitenabruf fehlgeschlagen: {}", response.status()).into());
         }
@@ -110,45 +237,135 @@ impl MupLecture {
         Ok(rows_parsed)
     }
 
-    fn parse_lecture_table(lecture_table: Vec<Vec<(String, usize, Option<String>)>>, semester_group: String) -> Vec<MupLecture> {
-        let mut lectures = Vec::new();
+    /// Parses the timetable of a plan page into lectures per cell, without network access.
+    /// Cells that cannot be parsed are reported with their position instead of being dropped silently.
+    pub fn parse_html(body: &str) -> ParseReport {
+        let lecture_table = Self::extract_lecture_table(body).unwrap_or_default();
+        if lecture_table.is_empty() {
+            return ParseReport {
+                cells: vec![],
+                diagnostics: vec![ParseDiagnostic {
+                    row: 0,
+                    column: 0,
+                    content: String::new(),
+                    reason: "no timetable found (table with border-collapse and background-color:#F7F8F8)".to_string(),
+                }],
+            };
+        }
+
+        Self::parse_lecture_table(lecture_table)
+    }
+
+    fn parse_lecture_table(lecture_table: Vec<Vec<(String, usize, Option<String>)>>) -> ParseReport {
+        let mut report = ParseReport::default();
+        // Rows a column is still covered by a cell of a previous row (rowspan)
+        let mut covered: Vec<usize> = Vec::new();
 
         for (i, rows) in lecture_table.iter().enumerate() {
-            for (j, (content, rowspan, title)) in rows.iter().enumerate() {
-                if !content.is_empty() && i > 0 && j > 0 {
-                    if let Some(parsed_infos) = Self::parse_lecture_info(content) {
-                        for (name, prof, location, description) in parsed_infos {
-                            let (start_time, end_time) = Self::calc_lecture_hours(i, *rowspan, &LectureDay::from_index(j));
-                           
-                           let mut disc = String::new();
-                            match title {
-                                Some(titl) => { 
-                                    if titl.len() < 22 {
-                                        disc = format!("full Name: {}, {}, Day: {}", titl, description, LectureDay::from_index(j));
-                                    } else {
-                                        disc = format!("{},\nDay: {}", description, LectureDay::from_index(j));
-                                    }
-
-                                },
-                                None => {}
+            let mut j = 0;
+            for (content, rowspan, title) in rows {
+                // Cells spanning several rows are missing in the following rows
+                while covered.get(j).is_some_and(|rows| *rows > 0) {
+                    j += 1;
+                }
+                if covered.len() <= j {
+                    covered.resize(j + 1, 0);
+                }
+                covered[j] = *rowspan;
+                let column = j;
+                j += 1;
+
+                if content.is_empty() || i == 0 || column == 0 {
+                    continue;
+                }
+
+                let diagnostic = |reason: &str| ParseDiagnostic {
+                    row: i,
+                    column,
+                    content: content.clone(),
+                    reason: reason.to_string(),
+                };
+
+                // The week marker is not part of the lecture name
+                let (parity, lecture) = Self::split_week_parity(content);
+                let Some(parsed_infos) = Self::parse_lecture_info(&lecture) else {
+                    report.diagnostics.push(diagnostic(&Self::diagnose(content)));
+                    continue;
+                };
+                let Some((start, end)) = Self::calc_lecture_hours(i, *rowspan) else {
+                    report.diagnostics.push(diagnostic("row is not one of the six time slots"));
+                    continue;
+                };
+                let Some(weekday) = LectureDay::weekday(column) else {
+                    report.diagnostics.push(diagnostic("column is not a weekday"));
+                    continue;
+                };
+
+                for (name, _prof, location, description) in parsed_infos {
+                   let mut disc = String::new();
+                    match title {
+                        Some(titl) => { 
+                            if titl.len() < 22 {
+                                disc = format!("full Name: {}, {}, Day: {}", titl, description, LectureDay::from_index(column));
+                            } else {
+                                disc = format!("{},\nDay: {}", description, LectureDay::from_index(column));
                             }
 
-                            lectures.push(MupLecture {
-                                name: format!("{}-{}", semester_group, name),
-                                location,
-                                description: disc,
-                                start: start_time,
-                                end: end_time,
-                            });
-                        }
-                    } else {
-                        println!("Keine gültigen Vorlesungsinformationen gefunden in: '{}'", content);
+                        },
+                        None => {}
                     }
+
+                    report.cells.push(ParsedCell {
+                        row: i,
+                        column,
+                        weekday,
+                        start,
+                        end,
+                        name,
+                        location,
+                        description: disc,
+                        parity,
+                    });
                 }
             }
+
+            for rows in covered.iter_mut() {
+                *rows = rows.saturating_sub(1);
+            }
         }
 
-        lectures
+        report
+    }
+
+    /// Explains why `parse_lecture_info` could not parse a cell.
+    fn diagnose(content: &str) -> String {
+        let re_location = Regex::new(r"BT(\d+)-(\d+)").unwrap();
+
+        if content.split_whitespace().count() < 3 {
+            "expected at least lecture, professor and room".to_string()
+        } else if !re_location.is_match(content) {
+            "no room found (BT<building>-<room>)".to_string()
+        } else {
+            "no professor or lecture name found".to_string()
+        }
+    }
+
+    /// Returns in which weeks the lecture takes place, e.g. "MAT A-Woche" only in A weeks,
+    /// and the cell content without the week marker.
+    fn split_week_parity(content: &str) -> (WeekParity, String) {
+        let re_parity = Regex::new(r"\b([AB])[- ]?Wo(?:che|chen)?\b|\(([AB])\)").unwrap();
+
+        let Some(captures) = re_parity.captures(content) else {
+            return (WeekParity::Every, content.to_string());
+        };
+        let parity = match captures.get(1).or_else(|| captures.get(2)).map(|marker| marker.as_str()) {
+            Some("A") => WeekParity::A,
+            Some("B") => WeekParity::B,
+            _ => WeekParity::Every,
+        };
+
+        let stripped = re_parity.replace(content, " ");
+        (parity, stripped.split_whitespace().collect::<Vec<_>>().join(" "))
     }
 
     fn parse_lecture_info(input: &str) -> Option<Vec<(String, String, String, String)>> {
@@ -245,154 +462,156 @@ impl MupLecture {
         Some(output_vec)
     }
 
-    fn weekday_to_english(weekday: Weekday) -> &'static str {
-    match weekday {
-        Weekday::Mon => "Monday",
-        Weekday::Tue => "Tuesday",
-        Weekday::Wed => "Wednesday",
-        Weekday::Thu => "Thursday",
-        Weekday::Fri => "Friday",
-        Weekday::Sat => "Saturday",
-        Weekday::Sun => "Sunday",
-        }
-    }
+    /// Returns start and end time of a lecture in the row `index` of the plan,
+    /// lectures spanning two rows end with the second time slot.
+    fn calc_lecture_hours(index: usize, rowspan: usize) -> Option<(NaiveTime, NaiveTime)> {
+        let time_slots = [
+            ("08:15", "09:45"),
+            ("10:00", "11:30"),
+            ("12:15", "13:45"),
+            ("14:00", "15:30"),
+            ("15:45", "17:15"),
+            ("17:30", "19:00"),
+        ];
 
-    fn calc_lecture_hours(index: usize, rowspan: usize, discription: &String) -> (NaiveDateTime, NaiveDateTime) {
-    let time_slots = [
-        ("08:15", "09:45"),
-        ("10:00", "11:30"),
-        ("12:15", "13:45"),
-        ("14:00", "15:30"),
-        ("15:45", "17:15"),
-        ("17:30", "19:00"),
-    ];
-
-    // Dummy-Datum verwenden, da nur Zeit relevant ist
-    let dummy_date = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
-
-    let date_today  = Local::now().date_naive();
-    let given_day: String = discription.chars().rev()
-        .take_while(|&c| c != ' ')
-        .collect::<String>()
-        .chars()
-        .rev()
-        .collect();
-
-    //println!("Tag: {}", given_day);
-
-    let mut date: NaiveDate = dummy_date;
-    let mut next_day = date_today;
-
-    for _ in 0..10 {
-
-        
-        if  given_day == Self::weekday_to_english(next_day.weekday()){
-            //println!("Tag gefunden: {}", next_day);
-            date = next_day;
-            //break;
-        }else {
-            next_day = next_day + ChronoDuration::days(1);
+        if index == 0 || index > time_slots.len() {
+            return None;
         }
 
-    }
+        let (start_str, end1_str) = time_slots[index - 1];
 
-    if index == 0 || index > time_slots.len() {
-        // Rückgabe von minimalen gültigen Zeitpunkten, alternativ kannst du Option<T> verwenden
-        return (
-            NaiveDateTime::new(date, NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-            NaiveDateTime::new(date, NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-        );
-    }
+        let end_str = match rowspan {
+            2 if index < time_slots.len() => time_slots[index].1,
+            _ => end1_str, // Fallback auf einfachen Block
+        };
 
-    let (start_str, end1_str) = time_slots[index - 1];
+        let start_time = NaiveTime::parse_from_str(start_str, "%H:%M").ok()?;
+        let end_time = NaiveTime::parse_from_str(end_str, "%H:%M").ok()?;
 
-    let end_str = match rowspan {
-        1 => end1_str,
-        2 => {
-            if index < time_slots.len() {
-                time_slots[index].1
-            } else {
-                end1_str // Falls rowspan zu lang ist: Zeit des aktuellen Blocks
-            }
-        }
-        _ => end1_str, // Fallback auf einfachen Block
-    };
-
-    let start_time = NaiveTime::parse_from_str(start_str, "%H:%M").unwrap();
-    let end_time = NaiveTime::parse_from_str(end_str, "%H:%M").unwrap();
-
-    (
-        NaiveDateTime::new(date, start_time),
-        NaiveDateTime::new(date, end_time),
-    )
-}
+        Some((start_time, end_time))
+    }
 
     /*########################################
     Haw bot Interface
     ########################################*/
 
-    fn save_struct_to_json(structs: &Vec<Vec<MupLecture>>) -> std::io::Result<()> {
-        let base_path = dirs::cache_dir().unwrap().join("hawhhcalendarbot-cli/eventdata/maschienenbau-und-produktion/");
+    /// Returns the module name of a semester group, e.g. `b-mt1` for the plan `B_MT1.php`.
+    pub fn module_name(semester_group: &str) -> String {
+        semester_group.to_lowercase().replace(['_', ' '], "-")
+    }
+
+    /// Stores every semester group as module `<eventdata>/maschienenbau-und-produktion/<group>.json`,
+    /// in the same format as the modules of the other departments.
+    pub(crate) fn save_struct_to_json(plans: Vec<GroupPlan>, eventdata_path: &Path) -> std::io::Result<()> {
+        let base_path = eventdata_path.join(MUP_DEPARTMENT);
         fs::create_dir_all(&base_path)?;
-        
 
-        for semester in structs {
-            for (index,lectures) in semester.into_iter().enumerate() {
-                
-            let path = base_path.join(format!("{}.json",lectures.name.chars().map(|c| if c == ' ' { '-' } else { c }).collect::<String>()/*SemesterGroup::from_index(index)*/));
-            
+        for (semester_group, lectures) in plans {
+            let path = base_path.join(format!("{}.json", Self::module_name(&semester_group)));
+            let events: Vec<HawEventEntry> = lectures.into_iter().map(HawEventEntry::from).collect();
 
             let file = File::create(path)?;
             let writer = BufWriter::new(file);
-            serde_json::to_writer_pretty(writer, lectures)?;
-            }
+            serde_json::to_writer_pretty(writer, &events)?;
         }
-        
+
+        crate::cache::write_timestamp(&base_path)?;
         Ok(())
     }
 
-    pub fn load_struct_from_json() -> std::io::Result<Vec<Vec<MupLecture>>> {
-        let path = dirs::cache_dir().unwrap().join("hawhhcalendarbot-cli/eventdata/mechatronik/");
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let data = serde_json::from_reader(reader)?;
-        Ok(data)
+    /// Scrapes the plans of the subscribed semester groups (all if none are subscribed),
+    /// returned with the name of their group. The groups are discovered on the plan index page.
+    /// A group that cannot be scraped is skipped with a warning and returned in the list of failed groups,
+    /// it only fails if no group could be scraped.
+    pub fn fetch_all_plans(user: String, password: String) -> Result<(Vec<GroupPlan>, Vec<String>), Box<dyn Error>> {
+        let groups = Self::discover_groups(&user, &password)?;
+        if let Some(cache_dir) = crate::cache::cache_dir() {
+            MupGroup::save_all(&groups, &cache_dir)?;
+        }
+
+        let subscribed = Config::load_config().get_mup_groups();
+        for module in &subscribed {
+            if !groups.iter().any(|group| &group.module() == module) {
+                eprintln!("Warning: subscribed MuP group '{}' is not on the plan index anymore, see 'mup groups'", module);
+            }
+        }
+
+        let mut lectures = Vec::new();
+        let mut failed = Vec::new();
+        let mut errors: Vec<Box<dyn Error>> = Vec::new();
+
+        for group in groups {
+            if !subscribed.is_empty() && !subscribed.contains(&group.module()) {
+                continue;
+            }
+
+            match Self::scrape_lecture_plan(user.clone(), password.clone(), group.url, group.id.clone()) {
+                Ok(plan) => lectures.push((group.id, plan)),
+                Err(e) => {
+                    eprintln!("Warning: cant fetch the MuP plan of {}, cause: {}", group.id, e);
+                    failed.push(group.id);
+                    errors.push(e);
+                }
+            }
+        }
+
+        if lectures.is_empty() && !errors.is_empty() {
+            return Err(errors.remove(0));
+        }
+
+        Ok((lectures, failed))
     }
 
-    pub fn fetch_all_plans(user: String, password: String) -> Result<Vec<Vec<MupLecture>>, Box<dyn Error>> {
-        let base_url = "https://www.mp.haw-hamburg.de/auth/vorlesungsplan/";
-        let mut urls = Self::generate_urls(base_url, "B_MT", ".php", 7);
-
-        let mut lectures: Vec<Vec<MupLecture>> = Vec::new();
-
-        let all_urls: Vec<String> = vec![
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/1a.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/1en.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/2a.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/2b.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/2c.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/3a.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/4DM.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/4ET.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/4EK.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/4P.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/5_6DM.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/5_6ET.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/5_6EK.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/5_6P.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/Master_BS.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/Master_NE.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/Master_P.php"),
-    String::from("https://www.mp.haw-hamburg.de/auth/vorlesungsplan/Master_KP.php"),
-        ];
-        urls.extend(all_urls);
+    /// Returns the semester groups linked on the plan index page.
+    pub fn discover_groups(user: &str, password: &str) -> Result<Vec<MupGroup>, Box<dyn Error>> {
+        let body = Self::fetch_html(user.to_string(), password.to_string(), MUP_PLAN_INDEX)?;
+        let groups = Self::parse_group_index(&body, MUP_PLAN_INDEX);
+
+        if groups.is_empty() {
+            return Err(format!("Found no semester groups on {}", MUP_PLAN_INDEX).into());
+        }
+        Ok(groups)
+    }
+
+    /// Parses the links to the plans of the semester groups, e.g. `<a href="B_MT1.php">Mechatronik 1. Semester</a>`.
+    /// Relative links are resolved against `base_url`, links to other directories are ignored.
+    pub fn parse_group_index(body: &str, base_url: &str) -> Vec<MupGroup> {
+        let document = Html::parse_document(body);
+        let link_selector = Selector::parse("a[href]").unwrap();
+        let mut groups: Vec<MupGroup> = Vec::new();
+
+        for link in document.select(&link_selector) {
+            let href = link.value().attr("href").unwrap_or_default().trim();
+            let href = href.split(['?', '#']).next().unwrap_or_default();
+
+            let url = if href.starts_with("http://") || href.starts_with("https://") {
+                href.to_string()
+            } else {
+                format!("{}{}", base_url, href.trim_start_matches("./"))
+            };
+            let Some(file) = url.strip_prefix(base_url) else {
+                continue;
+            };
+            if file.contains('/') || !file.ends_with(".php") || file == "index.php" {
+                continue;
+            }
 
-        for (index, url) in urls.into_iter().enumerate() {
-            let semester_name = Self::extract_last_segment(&url);
-            lectures.push(Self::scrape_lecture_plan(user.clone(), password.clone(), url.clone(), semester_name)?);
+            let id = Self::extract_last_segment(file);
+            if groups.iter().any(|group| group.id == id) {
+                continue;
+            }
+
+            let text = link.text().collect::<Vec<_>>().join(" ");
+            let name = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+            groups.push(MupGroup {
+                name: if name.is_empty() { id.clone() } else { name },
+                id,
+                url,
+            });
         }
 
-        Ok(lectures)
+        groups
     }
 
     fn extract_last_segment(path: &str) -> String {
@@ -405,21 +624,34 @@ impl MupLecture {
 
 
 
-    pub fn fetch_one_semester(user: String, password: String, semester_name: String) -> Result<Vec<MupLecture>, Box<dyn Error>> {
-        let base_url = "https://www.mp.haw-hamburg.de/auth/vorlesungsplan/";
-        let urls = Self::generate_urls(base_url, "B_MT", ".php", 7);
+    /// Scrapes all MuP plans and stores them as department in the event data directory.
+    /// Groups that could not be scraped keep their plan from `previous_path`.
+    pub fn fetch_all_mup_plans_to_cache(user: String, password: String, previous_path: &Path, eventdata_path: &Path) -> Result<(), Box<dyn Error>> {
+        let (plans, failed) = Self::fetch_all_plans(user, password)?;
+        Self::save_struct_to_json(plans, eventdata_path)?;
 
-        let lectures = Self::scrape_lecture_plan(user.clone(), password.clone(), urls[0].clone(), semester_name)?;
-        Ok(lectures)
-    }
-
-    pub fn fetch_all_mup_plans_to_cache(user: String, password: String) -> Result<(), Box<dyn Error>> {
-        let plans = Self::fetch_all_plans(user, password)?;
-        Self::save_struct_to_json(&plans)?;
+        for semester_group in failed {
+            let file = format!("{}.json", Self::module_name(&semester_group));
+            let previous = previous_path.join(MUP_DEPARTMENT).join(&file);
+            if previous.exists() {
+                fs::copy(previous, eventdata_path.join(MUP_DEPARTMENT).join(file))?;
+            }
+        }
         Ok(())
     }
 }
 
+impl From<MupLecture> for HawEventEntry {
+    fn from(lecture: MupLecture) -> Self {
+        HawEventEntry {
+            name: lecture.name,
+            location: lecture.location,
+            description: lecture.description,
+            start: lecture.start,
+            end: lecture.end,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub(crate) enum LectureDay {
@@ -427,6 +659,19 @@ pub(crate) enum LectureDay {
 }
 
 impl LectureDay {
+    /// Returns the weekday of the column `index` of the plan.
+    fn weekday(index: usize) -> Option<Weekday> {
+        match index {
+            1 => Some(Weekday::Mon),
+            2 => Some(Weekday::Tue),
+            3 => Some(Weekday::Wed),
+            4 => Some(Weekday::Thu),
+            5 => Some(Weekday::Fri),
+            6 => Some(Weekday::Sat),
+            _ => None,
+        }
+    }
+
     fn from_index(index: usize) -> String {
         match index {
             1 => String::from("Monday"),
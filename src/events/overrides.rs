@@ -0,0 +1,180 @@
+use std::{error::Error, fmt};
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::{
+    events::{event::EventMeta, haw_event::HawEventEntry},
+    json_parser::{escape, unescape},
+};
+
+/// Format of occurrence start times in the config and on the command line.
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Change applied to a single occurrence.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+#[serde(tag = "kind", content = "value", rename_all = "camelCase")]
+pub enum Action {
+    /// Hide the occurrence
+    Remove,
+    Rename(String),
+    /// Move the occurrence to a new start and end
+    Time(NaiveDateTime, NaiveDateTime),
+    Location(String),
+    /// Append a note to the description
+    Note(String),
+}
+
+impl Action {
+    fn kind(&self) -> &'static str {
+        match self {
+            Action::Remove => "remove",
+            Action::Rename(_) => "rename",
+            Action::Time(_, _) => "time",
+            Action::Location(_) => "location",
+            Action::Note(_) => "note",
+        }
+    }
+}
+
+/// Override of a single occurrence, identified by its module and original start.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+pub struct Override {
+    /// Module in the format "department:module".
+    pub module: String,
+    /// Original start of the occurrence.
+    pub start: NaiveDateTime,
+    pub action: Action,
+}
+
+impl Override {
+    /// Key of the occurrence in the config. Overrides sharing a key belong to the same occurrence.
+    pub fn occurrence_key(module: &str, start: NaiveDateTime) -> String {
+        format!("{}|{}|", module, start.format(DATETIME_FORMAT))
+    }
+
+    /// Key of the override in the config. Setting an override replaces the one with the same key.
+    pub fn key(&self) -> String {
+        format!("{}{}", Self::occurrence_key(&self.module, self.start), self.action.kind())
+    }
+
+    /// Encodes the override as config entry, e.g. "informatik:bai3-ad|2025-06-02T08:15|location|BT7-01.12".
+    pub fn to_entry(&self) -> String {
+        match &self.action {
+            Action::Remove => self.key(),
+            Action::Rename(value) | Action::Location(value) | Action::Note(value) => {
+                format!("{}|{}", self.key(), escape(value))
+            }
+            Action::Time(start, end) => {
+                format!("{}|{}|{}", self.key(), start.format(DATETIME_FORMAT), end.format(DATETIME_FORMAT))
+            }
+        }
+    }
+
+    /// Decodes a config entry created by `to_entry`.
+    pub fn from_entry(entry: &str) -> Result<Override, Box<dyn Error>> {
+        let fields: Vec<&str> = entry.split('|').collect();
+        let error = || format!("Invalid event override '{}'", entry);
+
+        let (module, start, kind, values) = match fields.as_slice() {
+            [module, start, kind, values @ ..] => (module, start, kind, values),
+            _ => return Err(error().into()),
+        };
+
+        let action = match (*kind, values) {
+            ("remove", []) => Action::Remove,
+            ("rename", [value]) => Action::Rename(unescape(value).ok_or_else(error)?),
+            ("location", [value]) => Action::Location(unescape(value).ok_or_else(error)?),
+            ("note", [value]) => Action::Note(unescape(value).ok_or_else(error)?),
+            ("time", [start, end]) => Action::Time(parse_datetime(start)?, parse_datetime(end)?),
+            _ => return Err(error().into()),
+        };
+
+        Ok(Override {
+            module: module.to_string(),
+            start: parse_datetime(start)?,
+            action,
+        })
+    }
+
+    /// Applies the override to the occurrence. Returns `None` if the occurrence is removed.
+    fn apply(&self, mut event: HawEventEntry) -> Option<HawEventEntry> {
+        match &self.action {
+            Action::Remove => return None,
+            Action::Rename(name) => event.name = name.clone(),
+            Action::Time(start, end) => {
+                event.start = *start;
+                event.end = *end;
+            }
+            Action::Location(location) => event.location = location.clone(),
+            Action::Note(note) => {
+                if !event.description.is_empty() {
+                    event.description.push_str("\n\n");
+                }
+                event.description.push_str(&format!("Note: {}", note));
+            }
+        }
+
+        Some(event)
+    }
+}
+
+impl fmt::Display for Override {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: ", self.module, self.start.format("%a %d.%m.%Y %H:%M"))?;
+
+        match &self.action {
+            Action::Remove => write!(f, "removed"),
+            Action::Rename(name) => write!(f, "renamed to '{}'", name),
+            Action::Time(start, end) => write!(
+                f,
+                "moved to {} {}-{}",
+                start.format("%a %d.%m.%Y"),
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            ),
+            Action::Location(location) => write!(f, "relocated to {}", location),
+            Action::Note(note) => write!(f, "note '{}'", note),
+        }
+    }
+}
+
+/// Applies all overrides of the module to its events. Overrides are matched by the original start.
+pub fn apply(meta: &EventMeta, events: Vec<HawEventEntry>, overrides: &[Override]) -> Vec<HawEventEntry> {
+    let module = format!("{}:{}", meta.department, meta.module);
+    let module_overrides: Vec<&Override> = overrides.iter().filter(|event_override| event_override.module == module).collect();
+    if module_overrides.is_empty() {
+        return events;
+    }
+
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let start = event.start;
+            module_overrides
+                .iter()
+                .filter(|event_override| event_override.start == start)
+                .try_fold(event, |event, event_override| event_override.apply(event))
+        })
+        .collect()
+}
+
+/// Parses the overrides stored in the config, skipping invalid entries.
+pub fn from_entries(entries: &[String]) -> Vec<Override> {
+    entries
+        .iter()
+        .filter_map(|entry| match Override::from_entry(entry) {
+            Ok(event_override) => Some(event_override),
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parses an occurrence start like "2025-06-02T08:15" or "2025-06-02 08:15".
+pub fn parse_datetime(value: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(&value.replacen(' ', "T", 1), DATETIME_FORMAT)
+        .map_err(|_| format!("Failed to parse date and time '{}'. Expected format: YYYY-MM-DDTHH:MM", value))
+}
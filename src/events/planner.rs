@@ -0,0 +1,118 @@
+use std::{collections::BTreeSet, error::Error, fmt};
+
+use chrono::{Datelike, NaiveTime, Weekday};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::events::{conflicts, schedule::ModuleEvents};
+
+/// Upper bound for the number of combinations that are evaluated.
+const MAX_COMBINATIONS: usize = 100_000;
+
+/// Criteria to rank plans by. Plans are always ranked by overlap first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Criterion {
+    /// Fewer weekdays on campus
+    Days,
+    /// Fewer lectures starting before the early threshold
+    Early,
+}
+
+/// A combination of modules with its ranking properties.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Plan {
+    /// Modules in the format "department:module".
+    pub modules: Vec<String>,
+    /// Sum of all overlaps between the modules in minutes.
+    pub overlap_minutes: i64,
+    /// Weekdays with at least one event.
+    pub campus_days: Vec<String>,
+    /// Number of events starting before the early threshold.
+    pub early_starts: usize,
+}
+
+impl Plan {
+    fn new(combination: &[&ModuleEvents], early_before: NaiveTime) -> Self {
+        let module_events: Vec<ModuleEvents> = combination.iter().map(|module| (*module).clone()).collect();
+        let overlap_minutes = conflicts::find_conflicts(&module_events)
+            .iter()
+            .map(|conflict| conflict.total_minutes)
+            .sum();
+
+        let events = combination.iter().flat_map(|(_, events)| events.iter());
+        let campus_days: BTreeSet<u32> = events
+            .clone()
+            .map(|event| event.start.weekday().num_days_from_monday())
+            .collect();
+        let early_starts = events.filter(|event| event.start.time() < early_before).count();
+
+        Plan {
+            modules: combination
+                .iter()
+                .map(|(meta, _)| format!("{}:{}", meta.department, meta.module))
+                .collect(),
+            overlap_minutes,
+            campus_days: campus_days
+                .into_iter()
+                .filter_map(|day| u8::try_from(day).ok().and_then(|day| Weekday::try_from(day).ok()))
+                .map(|day| day.to_string())
+                .collect(),
+            early_starts,
+        }
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Modules: {}", self.modules.join(", "))?;
+        writeln!(f, "Overlap: {} min", self.overlap_minutes)?;
+        writeln!(f, "Days on campus: {} ({})", self.campus_days.len(), self.campus_days.join(", "))?;
+        write!(f, "Early starts: {}", self.early_starts)
+    }
+}
+
+/// Evaluates every combination of the required modules with one module of each group
+/// and returns the plans ranked by overlap and the given criteria.
+pub fn plan(
+    required: &[ModuleEvents],
+    groups: &[Vec<ModuleEvents>],
+    criteria: &[Criterion],
+    early_before: NaiveTime,
+) -> Result<Vec<Plan>, Box<dyn Error>> {
+    if groups.iter().any(|group| group.is_empty()) {
+        return Err("Every group needs at least one module to choose from".into());
+    }
+
+    let combinations = groups
+        .iter()
+        .try_fold(1usize, |count, group| count.checked_mul(group.len()))
+        .filter(|count| *count <= MAX_COMBINATIONS)
+        .ok_or_else(|| format!("Too many combinations to evaluate (maximum {})", MAX_COMBINATIONS))?;
+
+    let mut plans = Vec::with_capacity(combinations);
+    for index in 0..combinations {
+        // Decode the index into one choice per group
+        let mut remaining = index;
+        let mut combination: Vec<&ModuleEvents> = required.iter().collect();
+        for group in groups {
+            combination.push(&group[remaining % group.len()]);
+            remaining /= group.len();
+        }
+
+        plans.push(Plan::new(&combination, early_before));
+    }
+
+    plans.sort_by(|a, b| {
+        let key = |plan: &Plan| -> Vec<i64> {
+            let mut key = vec![plan.overlap_minutes];
+            key.extend(criteria.iter().map(|criterion| match criterion {
+                Criterion::Days => plan.campus_days.len() as i64,
+                Criterion::Early => plan.early_starts as i64,
+            }));
+            key
+        };
+        key(a).cmp(&key(b)).then_with(|| a.modules.cmp(&b.modules))
+    });
+
+    Ok(plans)
+}
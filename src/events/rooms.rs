@@ -0,0 +1,91 @@
+use std::{collections::BTreeSet, fmt};
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+
+use crate::events::haw_event::HawEventEntry;
+
+/// Room that is free during the whole requested time window.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct FreeRoom {
+    pub room: String,
+    /// Start of the next event in the room on the same day, if any.
+    pub free_until: Option<NaiveDateTime>,
+}
+
+impl fmt::Display for FreeRoom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.free_until {
+            Some(until) => write!(f, "{:<16} free until {}", self.room, until.format("%H:%M")),
+            None => write!(f, "{:<16} free for the rest of the day", self.room),
+        }
+    }
+}
+
+/// Splits a location like "BT7-01.12, BT7-01.13" into normalized room names.
+/// Locations without a digit ("Online", "extern") are not rooms.
+pub fn rooms_of(location: &str) -> Vec<String> {
+    location
+        .split([',', ';', '&'])
+        .map(|room| room.trim().to_uppercase())
+        .filter(|room| room.chars().any(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+/// Checks whether the room belongs to the building, e.g. "BT7-01.12" to "BT7" but not to "BT70".
+pub fn in_building(room: &str, building: &str) -> bool {
+    let building = building.trim().to_uppercase();
+    room.strip_prefix(&building)
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric()))
+}
+
+/// Returns the known rooms that have no event overlapping the time window on the given date,
+/// optionally limited to a building.
+pub fn find_free_rooms<'a>(
+    known_rooms: impl IntoIterator<Item = &'a String>,
+    events: &[&HawEventEntry],
+    date: NaiveDate,
+    between: (NaiveTime, NaiveTime),
+    building: Option<&str>,
+) -> Vec<FreeRoom> {
+    let window_start = date.and_time(between.0);
+    let window_end = date.and_time(between.1);
+
+    let known_rooms: BTreeSet<&String> = known_rooms
+        .into_iter()
+        .filter(|room| building.is_none_or(|building| in_building(room, building)))
+        .collect();
+
+    let occupied: BTreeSet<String> = events
+        .iter()
+        .filter(|event| event.start < window_end && event.end > window_start)
+        .flat_map(|event| rooms_of(&event.location))
+        .collect();
+
+    known_rooms
+        .into_iter()
+        .filter(|room| !occupied.contains(*room))
+        .map(|room| {
+            let free_until = events
+                .iter()
+                .filter(|event| event.start >= window_end && event.start.date() == date)
+                .filter(|event| rooms_of(&event.location).contains(room))
+                .map(|event| event.start)
+                .min();
+
+            FreeRoom {
+                room: room.clone(),
+                free_until,
+            }
+        })
+        .collect()
+}
+
+/// Returns the events of the room between `from` and `to` (both inclusive), keeping their order.
+pub fn room_schedule(room_events: &[&HawEventEntry], from: NaiveDate, to: NaiveDate) -> Vec<HawEventEntry> {
+    room_events
+        .iter()
+        .filter(|event| (from..=to).contains(&event.start.date()))
+        .map(|event| (*event).clone())
+        .collect()
+}
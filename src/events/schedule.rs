@@ -0,0 +1,83 @@
+use std::{collections::BTreeMap, error::Error};
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::events::{
+    event::{Event, EventMeta},
+    haw_event::HawEventEntry,
+    ics_event::IcsEvent,
+};
+
+/// All events of a single module.
+pub type ModuleEvents = (EventMeta, Vec<HawEventEntry>);
+
+/// Returns the events of all configured modules and ICS sources for a given date, sorted by start time.
+pub fn get_all_events_for_date(date: NaiveDate) -> Result<Vec<HawEventEntry>, Box<dyn Error>> {
+    let ics_events = ics_events_in_range(date, date)?;
+
+    // Without module events the day can still be filled by ics sources
+    let mut events = match HawEventEntry::get_all_events_for_date(date) {
+        Ok(events) => events,
+        Err(_) if !ics_events.is_empty() => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    events.extend(ics_events.into_iter().map(HawEventEntry::from));
+
+    sort_events(&mut events);
+    Ok(events)
+}
+
+/// Returns the events of all configured modules and ICS sources starting between `from` and `to`
+/// (both inclusive), sorted by start time.
+pub fn get_events_in_range(from: NaiveDate, to: NaiveDate) -> Result<Vec<HawEventEntry>, Box<dyn Error>> {
+    let mut events = HawEventEntry::get_events_in_range(from, to)?;
+    let ics_events = ics_events_in_range(from, to)?;
+    events.extend(ics_events.into_iter().map(HawEventEntry::from));
+
+    sort_events(&mut events);
+    Ok(events)
+}
+
+/// Returns the events of all ICS sources starting between `from` and `to`.
+/// A missing or broken source only prints a warning, so the other sources are still shown.
+fn ics_events_in_range(from: NaiveDate, to: NaiveDate) -> Result<Vec<IcsEvent>, Box<dyn Error>> {
+    let mut events = Vec::new();
+
+    for source in IcsEvent::get_configured_modules()? {
+        match IcsEvent::get_module_events_in_range(std::slice::from_ref(&source), from, to) {
+            Ok(source_events) => events.extend(source_events),
+            Err(e) => eprintln!("Warning: skipping ICS source '{}': {}", source.module, e),
+        }
+    }
+
+    Ok(events)
+}
+
+/// Loads all events of the given modules, keeping them grouped by module.
+pub fn load_module_events(event_descriptor: &[EventMeta]) -> Result<Vec<ModuleEvents>, Box<dyn Error>> {
+    event_descriptor
+        .iter()
+        .map(|event_meta| Ok((event_meta.clone(), HawEventEntry::get_events_for_module(event_meta)?)))
+        .collect()
+}
+
+fn sort_events(events: &mut [HawEventEntry]) {
+    events.sort_by(|a, b| (a.start, a.end, &a.name).cmp(&(b.start, b.end, &b.name)));
+}
+
+/// Groups events by the day they start on. Events keep their order within a day.
+pub fn group_by_day(events: Vec<HawEventEntry>) -> BTreeMap<NaiveDate, Vec<HawEventEntry>> {
+    let mut days: BTreeMap<NaiveDate, Vec<HawEventEntry>> = BTreeMap::new();
+
+    for event in events {
+        days.entry(event.start.date()).or_default().push(event);
+    }
+
+    days
+}
+
+/// Returns monday and sunday of the week containing `date`, shifted by `offset` weeks.
+pub fn week_range(date: NaiveDate, offset: i64) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday().into()) + Duration::weeks(offset);
+    (monday, monday + Duration::days(6))
+}
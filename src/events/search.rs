@@ -0,0 +1,145 @@
+use std::{collections::BTreeSet, error::Error, fmt};
+
+use serde::Serialize;
+
+use crate::events::{event_index::EventIndex, haw_event::HawEventEntry};
+
+/// Module matching a search query.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Module in the format "department:module", ready to pass to `events add`.
+    pub module: String,
+    /// Relevance of the match, higher is better.
+    pub score: u32,
+    /// What matched the query.
+    pub matched: String,
+}
+
+impl fmt::Display for SearchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<40} {}", self.module, self.matched)
+    }
+}
+
+/// Searches all modules of every department in the event index.
+/// Module names are matched fuzzily, event names and descriptions by words.
+pub fn search(index: &EventIndex, query: &str) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".into());
+    }
+
+    let mut results: Vec<SearchResult> = index
+        .iter_modules()
+        .filter_map(|(department, module, events)| {
+            let (score, matched) = score_module(&query, module, events)?;
+            Some(SearchResult {
+                module: format!("{}:{}", department, module),
+                score,
+                matched,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.module.cmp(&b.module)));
+    Ok(results)
+}
+
+/// Scores a module by its name, event names and descriptions. Returns the best match.
+fn score_module(query: &str, module_name: &str, events: &[HawEventEntry]) -> Option<(u32, String)> {
+    let mut best = fuzzy_score(query, module_name).map(|score| (score, "module name".to_string()));
+
+    // Event names beat weak module name matches
+    let names: BTreeSet<&str> = events.iter().map(|event| event.name.as_str()).collect();
+    let matching_name = names.into_iter().find(|name| contains_words(query, name));
+    if let Some(name) = matching_name.filter(|_| best.as_ref().is_none_or(|(score, _)| *score < 70)) {
+        best = Some((70, format!("event '{}'", name)));
+    }
+
+    if best.is_none() {
+        let event = events.iter().find(|event| contains_words(query, &event.description))?;
+        best = Some((40, format!("description of '{}'", event.name)));
+    }
+
+    best
+}
+
+/// Scores how well the lowercase `query` matches `candidate`, from 0 to 100.
+/// Accepts substrings, subsequences ("bsad" for "bai3-ad") and small typos.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate = candidate.to_lowercase();
+
+    if candidate == query {
+        return Some(100);
+    }
+    if candidate.starts_with(query) {
+        return Some(90);
+    }
+    if let Some(position) = candidate.find(query) {
+        // Matches at the start of a segment like "ad" in "bai3-ad" are more relevant
+        let at_boundary = candidate[..position].ends_with(['-', '_', ' ']);
+        return Some(if at_boundary { 85 } else { 75 });
+    }
+
+    // Typos, compared against the whole name and its segments
+    let max_distance = if query.chars().count() >= 6 { 2 } else { 1 };
+    let distance = std::iter::once(candidate.as_str())
+        .chain(candidate.split(['-', '_', ' ']))
+        .map(|segment| levenshtein(query, segment))
+        .min()
+        .unwrap_or(usize::MAX);
+    if query.chars().count() >= 4 && distance <= max_distance {
+        return Some(70 - 10 * distance as u32);
+    }
+
+    subsequence_score(query, &candidate)
+}
+
+/// Scores a subsequence match, rewarding consecutive characters.
+fn subsequence_score(query: &str, candidate: &str) -> Option<u32> {
+    if query.chars().count() < 2 {
+        return None;
+    }
+
+    let mut candidate_chars = candidate.chars().enumerate();
+    let mut last_index: Option<usize> = None;
+    let mut consecutive = 0;
+
+    for c in query.chars() {
+        let (index, _) = candidate_chars.find(|(_, candidate_char)| *candidate_char == c)?;
+        if last_index.is_some_and(|last| last + 1 == index) {
+            consecutive += 1;
+        }
+        last_index = Some(index);
+    }
+
+    let pairs = query.chars().count() - 1;
+    Some(30 + (20 * consecutive / pairs) as u32)
+}
+
+/// Checks whether every word of the query appears in the text.
+fn contains_words(query: &str, text: &str) -> bool {
+    let text = text.to_lowercase();
+    query.split_whitespace().all(|word| text.contains(word))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if a_char == *b_char {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
@@ -4,11 +4,27 @@ mod tests {
     use std::io::Write;
     use std::path::PathBuf;
 
-    use chrono::{NaiveDateTime};
+    use chrono::{NaiveDate, NaiveDateTime};
     use tempfile::tempdir;
 
+    use crate::events::changes::{self, Change};
+    use crate::events::conflicts;
+    use crate::events::error::EventError;
     use crate::events::event::{Event, EventMeta};
+    use crate::events::event_index::EventIndex;
+    use crate::events::free_slots;
     use crate::events::haw_event::HawEventEntry;
+    use crate::events::ics_event::IcsEvent;
+    use crate::events::ics_export;
+    use crate::events::mup_scraper::{MupLecture, MUP_DEPARTMENT};
+    use crate::events::overrides::{self, Action, Override};
+    use crate::events::planner::{self, Criterion};
+    use crate::events::rooms;
+    use crate::events::schedule;
+    use crate::events::search;
+    use crate::events::upcoming;
+    use crate::json_parser::isolate_config;
+    use crate::semester::{Semester, Term, WeekParity};
 
     fn standard_event() -> HawEventEntry {
         HawEventEntry {
@@ -30,6 +46,7 @@ mod tests {
     #[test]
     fn test_load_local_data_no_data() {
         // arrange
+        isolate_config();
         let temp_dir = tempdir().unwrap();
         let invalid_path = temp_dir.path().join("non_existent_data");
         let event_meta = standard_event_meta();
@@ -44,6 +61,7 @@ mod tests {
     #[test]
     fn test_load_local_data_with_test_data() {
         // arrange
+        isolate_config();
         let temp_dir = tempdir().unwrap();
         let test_path = temp_dir.path();
         let eventdata_dir = create_test_eventdata(test_path);
@@ -69,6 +87,7 @@ mod tests {
     #[test]
     fn test_load_local_data_outdated_timestamp() {
         // arrange
+        isolate_config();
         let temp_dir = tempdir().unwrap();
         let test_path = temp_dir.path();
         let eventdata_dir = create_test_eventdata(test_path);
@@ -87,6 +106,823 @@ mod tests {
         assert!(result.is_err(), "Expected error due to outdated data and network fetch failure");
     }
 
+    #[test]
+    fn test_ics_export_is_stable() {
+        // arrange
+        let mut later_event = standard_event();
+        later_event.start += chrono::Duration::days(7);
+        later_event.end += chrono::Duration::days(7);
+        let events = vec![
+            (standard_event_meta(), later_event),
+            (standard_event_meta(), standard_event()),
+        ];
+        let mut reversed = events.clone();
+        reversed.reverse();
+
+        // act
+        let ics = ics_export::to_ics(&events);
+
+        // assert
+        assert_eq!(ics, ics_export::to_ics(&reversed), "Export should not depend on input order");
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("UID:20250601T081500-test-department-test-module-test-event@"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Berlin:20250601T081500\r\n"));
+        assert!(ics.contains("DTSTAMP:20250601T061500Z\r\n"), "DTSTAMP should be the start in UTC");
+        assert!(ics.contains("LOCATION:BT101\r\n"));
+        assert!(ics.find("20250601T081500") < ics.find("20250608T081500"));
+    }
+
+    #[test]
+    fn test_ics_export_escapes_and_folds() {
+        // arrange
+        let mut event = standard_event();
+        event.description = format!("Raum 1, Gruppe A; {}", "x".repeat(100));
+
+        // act
+        let ics = ics_export::to_ics(&[(standard_event_meta(), event)]);
+
+        // assert
+        assert!(ics.contains("DESCRIPTION:Raum 1\\, Gruppe A\\; "));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75), "Lines must be folded at 75 octets");
+    }
+
+    #[test]
+    fn test_ics_export_dtstamp_in_utc() {
+        // arrange
+        let events: Vec<(EventMeta, HawEventEntry)> = ["2025-01-15 08:15:00", "2025-10-25 08:15:00", "2025-10-27 08:15:00"]
+            .into_iter()
+            .map(|start| (standard_event_meta(), event_at("AD", start, start)))
+            .collect();
+
+        // act
+        let ics = ics_export::to_ics(&events);
+
+        // assert
+        assert!(ics.contains("DTSTAMP:20250115T071500Z\r\n"), "Winter time is UTC+1");
+        assert!(ics.contains("DTSTAMP:20251025T061500Z\r\n"), "Summer time ends on the last sunday of October");
+        assert!(ics.contains("DTSTAMP:20251027T071500Z\r\n"));
+    }
+
+    fn parse_datetime(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_ics_event_parse_file() {
+        // arrange
+        let path = PathBuf::from("./test_data/icsdata/test.ics");
+
+        // act
+        let result = IcsEvent::parse_file(&path);
+
+        // assert
+        assert!(result.is_ok(), "Failed to parse ICS file: {:?}", result.err());
+        let events = result.unwrap();
+        let starts: Vec<NaiveDateTime> = events.iter().map(|event| event.start).collect();
+        assert_eq!(starts, vec![
+            parse_datetime("2025-04-07 08:15:00"),
+            parse_datetime("2025-04-08 12:00:00"),
+            parse_datetime("2025-04-10 12:00:00"),
+            parse_datetime("2025-04-21 10:00:00"),
+            parse_datetime("2025-04-22 12:00:00"),
+            parse_datetime("2025-04-24 12:00:00"),
+            parse_datetime("2025-04-28 08:15:00"),
+            parse_datetime("2025-05-01 00:00:00"),
+        ]);
+
+        let first = &events[0];
+        assert_eq!(first.name, "Lerngruppe Mathe");
+        assert_eq!(first.location, "BT7 Raum 1, EG");
+        assert_eq!(first.description, "Wöchentliche Lerngruppe\nBitte Skript mitbringen und die Aufgaben vorbereiten");
+        assert_eq!(first.end, parse_datetime("2025-04-07 09:45:00"));
+
+        let moved = &events[3];
+        assert_eq!(moved.name, "Lerngruppe Mathe (verschoben)");
+        assert_eq!(moved.end, parse_datetime("2025-04-21 11:30:00"));
+
+        assert_eq!(events[7].end, parse_datetime("2025-05-02 00:00:00"), "All-day events should last one day");
+    }
+
+    #[test]
+    fn test_ics_event_parse_invalid() {
+        // arrange
+        let content = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Kaputt\nEND:VEVENT\nEND:VCALENDAR\n";
+
+        // act
+        let result = IcsEvent::parse(content);
+
+        // assert
+        assert!(result.is_err(), "Expected error for VEVENT without DTSTART");
+    }
+
+    #[test]
+    fn test_ics_event_skips_unsupported_rrules() {
+        // arrange
+        let vevent = |summary: &str, rrule: &str| {
+            format!("BEGIN:VEVENT\nUID:{0}\nSUMMARY:{0}\nDTSTART:20250408T100000\nDTEND:20250408T110000\nRRULE:{1}\nEND:VEVENT\n", summary, rrule)
+        };
+        let content = format!(
+            "BEGIN:VCALENDAR\n{}{}{}{}END:VCALENDAR\n",
+            vevent("Wöchentlich", "FREQ=WEEKLY;COUNT=2;BYDAY=TU;WKST=MO"),
+            vevent("Zweiter Dienstag", "FREQ=MONTHLY;BYDAY=2TU;COUNT=3"),
+            vevent("Monatstag", "FREQ=MONTHLY;BYMONTHDAY=8,20;COUNT=3"),
+            vevent("Jährlich", "FREQ=YEARLY;BYMONTH=4;BYDAY=TU;COUNT=3"),
+        );
+
+        // act
+        let result = IcsEvent::parse(&content).unwrap();
+
+        // assert
+        let names: Vec<&str> = result.iter().map(|event| event.name.as_str()).collect();
+        assert_eq!(names, vec!["Wöchentlich", "Wöchentlich"], "Unsupported rules must not produce wrong occurrences");
+        assert_eq!(result[1].start, parse_datetime("2025-04-15 10:00:00"));
+    }
+
+    #[test]
+    fn test_ics_event_roundtrip_export() {
+        // arrange
+        let ics = ics_export::to_ics(&[(standard_event_meta(), standard_event())]);
+
+        // act
+        let result = IcsEvent::parse(&ics);
+
+        // assert
+        assert!(result.is_ok(), "Failed to parse exported ICS: {:?}", result.err());
+        let events: Vec<HawEventEntry> = result.unwrap().into_iter().map(HawEventEntry::from).collect();
+        assert_eq!(events, vec![standard_event()]);
+    }
+
+    #[test]
+    fn test_week_range() {
+        // arrange
+        let wednesday = chrono::NaiveDate::from_ymd_opt(2025, 7, 2).unwrap();
+
+        // act
+        let current = schedule::week_range(wednesday, 0);
+        let next = schedule::week_range(wednesday, 1);
+        let previous = schedule::week_range(wednesday, -1);
+
+        // assert
+        assert_eq!(current, (chrono::NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(), chrono::NaiveDate::from_ymd_opt(2025, 7, 6).unwrap()));
+        assert_eq!(next.0, chrono::NaiveDate::from_ymd_opt(2025, 7, 7).unwrap());
+        assert_eq!(previous.1, chrono::NaiveDate::from_ymd_opt(2025, 6, 29).unwrap());
+    }
+
+    #[test]
+    fn test_group_by_day() {
+        // arrange
+        let mut same_day_other_month = standard_event();
+        same_day_other_month.start = parse_datetime("2025-07-01 10:00:00");
+        same_day_other_month.end = parse_datetime("2025-07-01 11:30:00");
+        let mut later_same_day = standard_event();
+        later_same_day.start = parse_datetime("2025-06-01 12:15:00");
+        later_same_day.end = parse_datetime("2025-06-01 13:45:00");
+
+        // act
+        let days = schedule::group_by_day(vec![standard_event(), later_same_day.clone(), same_day_other_month.clone()]);
+
+        // assert
+        assert_eq!(days.len(), 2, "Events on the same day of different months must not be grouped");
+        let june = days.get(&chrono::NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()).unwrap();
+        assert_eq!(june, &vec![standard_event(), later_same_day]);
+        let july = days.get(&chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap()).unwrap();
+        assert_eq!(july, &vec![same_day_other_month]);
+    }
+
+    fn event_at(name: &str, start: &str, end: &str) -> HawEventEntry {
+        HawEventEntry {
+            name: name.to_string(),
+            location: "BT7".to_string(),
+            description: String::new(),
+            start: parse_datetime(start),
+            end: parse_datetime(end),
+        }
+    }
+
+    fn meta(module: &str) -> EventMeta {
+        EventMeta {
+            department: "informatik".to_string(),
+            module: module.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        // arrange
+        let modules = vec![
+            (meta("bai3-ad"), vec![
+                event_at("AD", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+                event_at("AD", "2025-06-09 08:15:00", "2025-06-09 09:45:00"),
+            ]),
+            (meta("bai3-bs"), vec![
+                event_at("BS", "2025-06-02 09:00:00", "2025-06-02 10:30:00"),
+                event_at("BS", "2025-06-09 09:45:00", "2025-06-09 11:15:00"),
+                event_at("BS", "2025-06-16 08:15:00", "2025-06-16 09:45:00"),
+            ]),
+            (meta("bai3-pm"), vec![
+                event_at("PM", "2025-06-02 08:00:00", "2025-06-02 08:30:00"),
+            ]),
+        ];
+
+        // act
+        let result = conflicts::find_conflicts(&modules);
+
+        // assert
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].modules, ("informatik:bai3-ad".to_string(), "informatik:bai3-bs".to_string()));
+        assert_eq!(result[0].overlaps.len(), 1, "Back-to-back events must not collide");
+        assert_eq!(result[0].total_minutes, 45);
+        assert_eq!(result[0].weeks, vec!["2025-W23".to_string()]);
+        assert_eq!(result[0].overlaps[0].first.name, "AD");
+        assert_eq!(result[1].modules, ("informatik:bai3-ad".to_string(), "informatik:bai3-pm".to_string()));
+        assert_eq!(result[1].overlaps[0].first.name, "AD", "Overlaps keep the module order");
+        assert_eq!(result[1].total_minutes, 15);
+    }
+
+    #[test]
+    fn test_find_conflicts_same_module() {
+        // arrange
+        let modules = vec![
+            (meta("bai3-ad"), vec![
+                event_at("AD Vorlesung", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+                event_at("AD Übung", "2025-06-02 09:00:00", "2025-06-02 10:30:00"),
+            ]),
+        ];
+
+        // act
+        let result = conflicts::find_conflicts(&modules);
+
+        // assert
+        assert!(result.is_empty(), "Events of the same module are no conflicts");
+    }
+
+    #[test]
+    fn test_plan_ranks_conflict_free_first() {
+        // arrange
+        let required = vec![
+            (meta("bai3-ad"), vec![event_at("AD", "2025-06-02 10:00:00", "2025-06-02 11:30:00")]),
+        ];
+        let groups = vec![vec![
+            (meta("bai3-pm1"), vec![event_at("PM1", "2025-06-02 11:00:00", "2025-06-02 12:30:00")]),
+            (meta("bai3-pm2"), vec![event_at("PM2", "2025-06-03 08:15:00", "2025-06-03 09:45:00")]),
+            (meta("bai3-pm3"), vec![event_at("PM3", "2025-06-02 12:15:00", "2025-06-02 13:45:00")]),
+        ]];
+
+        // act
+        let result = planner::plan(&required, &groups, &[Criterion::Days, Criterion::Early], chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap());
+
+        // assert
+        assert!(result.is_ok(), "Planning failed: {:?}", result.err());
+        let plans = result.unwrap();
+        assert_eq!(plans.len(), 3);
+        assert_eq!(plans[0].modules, vec!["informatik:bai3-ad".to_string(), "informatik:bai3-pm3".to_string()]);
+        assert_eq!(plans[0].campus_days, vec!["Mon".to_string()]);
+        assert_eq!(plans[1].modules[1], "informatik:bai3-pm2");
+        assert_eq!(plans[1].early_starts, 1);
+        assert_eq!(plans[2].overlap_minutes, 30);
+    }
+
+    #[test]
+    fn test_plan_rank_criteria_order() {
+        // arrange
+        let groups = vec![vec![
+            (meta("early-one-day"), vec![event_at("A", "2025-06-02 08:15:00", "2025-06-02 09:45:00")]),
+            (meta("late-two-days"), vec![
+                event_at("B", "2025-06-02 12:15:00", "2025-06-02 13:45:00"),
+                event_at("B", "2025-06-03 12:15:00", "2025-06-03 13:45:00"),
+            ]),
+        ]];
+        let early_before = chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+
+        // act
+        let by_days = planner::plan(&[], &groups, &[Criterion::Days], early_before).unwrap();
+        let by_early = planner::plan(&[], &groups, &[Criterion::Early], early_before).unwrap();
+
+        // assert
+        assert_eq!(by_days[0].modules, vec!["informatik:early-one-day".to_string()]);
+        assert_eq!(by_early[0].modules, vec!["informatik:late-two-days".to_string()]);
+    }
+
+    #[test]
+    fn test_find_free_slots() {
+        // arrange
+        let events = vec![
+            event_at("AD", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+            event_at("BS", "2025-06-02 09:00:00", "2025-06-02 10:30:00"),
+            event_at("PM", "2025-06-02 11:00:00", "2025-06-02 12:00:00"),
+            event_at("SE", "2025-06-02 18:00:00", "2025-06-02 20:00:00"),
+        ];
+        let days = vec![chrono::NaiveDate::from_ymd_opt(2025, 6, 2).unwrap(), chrono::NaiveDate::from_ymd_opt(2025, 6, 3).unwrap()];
+        let between = free_slots::parse_time_window("08:00-19:00").unwrap();
+
+        // act
+        let slots = free_slots::find_free_slots(&events, &days, between, chrono::Duration::minutes(20));
+
+        // assert
+        let slots: Vec<(NaiveDateTime, NaiveDateTime)> = slots.iter().map(|slot| (slot.start, slot.end)).collect();
+        assert_eq!(slots, vec![
+            (parse_datetime("2025-06-02 10:30:00"), parse_datetime("2025-06-02 11:00:00")),
+            (parse_datetime("2025-06-02 12:00:00"), parse_datetime("2025-06-02 18:00:00")),
+            (parse_datetime("2025-06-03 08:00:00"), parse_datetime("2025-06-03 19:00:00")),
+        ], "The 15 minute gap before the first event is shorter than the minimum");
+    }
+
+    #[test]
+    fn test_free_slot_parsing() {
+        assert_eq!(free_slots::parse_duration("45m"), Ok(chrono::Duration::minutes(45)));
+        assert_eq!(free_slots::parse_duration("1h30m"), Ok(chrono::Duration::minutes(90)));
+        assert_eq!(free_slots::parse_duration("90"), Ok(chrono::Duration::minutes(90)));
+        assert!(free_slots::parse_duration("1x").is_err());
+        assert!(free_slots::parse_time_window("19:00-08:00").is_err());
+        assert!(free_slots::parse_time_window("08:00").is_err());
+    }
+
+    #[test]
+    fn test_diff_changes() {
+        // arrange
+        let unchanged = event_at("AD", "2025-06-02 08:15:00", "2025-06-02 09:45:00");
+        let moved_before = event_at("AD", "2025-06-09 08:15:00", "2025-06-09 09:45:00");
+        let moved_after = event_at("AD", "2025-06-11 12:15:00", "2025-06-11 13:45:00");
+        let room_before = event_at("AD", "2025-06-16 08:15:00", "2025-06-16 09:45:00");
+        let mut room_after = room_before.clone();
+        room_after.location = "BT7-01.12".to_string();
+        let cancelled = event_at("AD Klausur", "2025-07-01 10:00:00", "2025-07-01 12:00:00");
+        let added = event_at("AD Übung", "2025-06-20 10:00:00", "2025-06-20 11:30:00");
+
+        let old = vec![unchanged.clone(), moved_before.clone(), room_before.clone(), cancelled.clone()];
+        let new = vec![unchanged, moved_after.clone(), room_after.clone(), added.clone()];
+
+        // act
+        let result = changes::diff(&old, &new);
+
+        // assert
+        assert_eq!(result, vec![
+            Change::Moved { before: moved_before, after: moved_after },
+            Change::Relocated { before: room_before, after: room_after },
+            Change::Added { event: added },
+            Change::Removed { event: cancelled },
+        ]);
+    }
+
+    #[test]
+    fn test_change_snapshots() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let cache_dir = temp_dir.path();
+        let eventdata_dir = create_test_eventdata(cache_dir);
+        let event_meta = vec![standard_event_meta()];
+        let previous = changes::read_modules(&eventdata_dir, &event_meta);
+
+        let mut moved = standard_event();
+        moved.location = "BT102".to_string();
+        fs::write(eventdata_dir.join("test-department/test-module.json"), serde_json::to_string(&vec![moved.clone()]).unwrap()).unwrap();
+
+        // act
+        changes::update_snapshots(cache_dir, &eventdata_dir, previous).unwrap();
+        let result = changes::load_changes(cache_dir, &event_meta).unwrap();
+
+        // assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].module, "test-department:test-module");
+        assert_eq!(result[0].changes, vec![Change::Relocated { before: standard_event(), after: moved }]);
+
+        // An unchanged refresh keeps the last detected change
+        let previous = changes::read_modules(&eventdata_dir, &event_meta);
+        changes::update_snapshots(cache_dir, &eventdata_dir, previous).unwrap();
+        assert_eq!(changes::load_changes(cache_dir, &event_meta).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_change_snapshots_skip_unparsable_previous_data() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let cache_dir = temp_dir.path();
+        let eventdata_dir = create_test_eventdata(cache_dir);
+        let event_meta = vec![standard_event_meta()];
+        let previous = vec![(standard_event_meta(), "[{\"name\": ".to_string())];
+
+        // act
+        let result = changes::update_snapshots(cache_dir, &eventdata_dir, previous);
+
+        // assert
+        assert!(result.is_ok(), "A broken previous version must not fail the refresh");
+        assert!(changes::load_changes(cache_dir, &event_meta).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_current_and_next_event() {
+        // arrange
+        let events = vec![
+            event_at("AD", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+            event_at("BS", "2025-06-02 10:00:00", "2025-06-02 11:30:00"),
+            event_at("SE", "2025-06-03 08:15:00", "2025-06-03 09:45:00"),
+        ];
+        let now = parse_datetime("2025-06-02 09:20:00");
+
+        // act
+        let current = upcoming::current(&events, now);
+        let next = upcoming::next(&events, now).unwrap();
+
+        // assert
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].event.name, "AD");
+        assert_eq!(current[0].ends_in_minutes, 25);
+        assert_eq!(current[0].compact(), "AD (BT7) until 09:45, 25min left");
+        assert_eq!(next.event.name, "BS");
+        assert_eq!(next.starts_in_minutes, 40);
+        assert_eq!(next.compact(), "BS (BT7) in 40min");
+
+        assert!(upcoming::current(&events, parse_datetime("2025-06-02 09:45:00")).is_empty());
+        assert!(upcoming::next(&events, parse_datetime("2025-06-03 08:15:00")).is_none());
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(upcoming::countdown(5), "5min");
+        assert_eq!(upcoming::countdown(65), "1h 05min");
+        assert_eq!(upcoming::countdown(3 * 24 * 60 + 125), "3d 2h");
+    }
+
+    #[test]
+    fn test_override_entry_roundtrip() {
+        // arrange
+        let event_override = Override {
+            module: "informatik:bai3-ad".to_string(),
+            start: parse_datetime("2025-06-02 08:15:00"),
+            action: Action::Note("Laptop, Stift | \"Skript\" 100%".to_string()),
+        };
+
+        // act
+        let entry = event_override.to_entry();
+        let result = Override::from_entry(&entry).unwrap();
+
+        // assert
+        assert!(!entry.contains([',', '"']));
+        assert_eq!(result, event_override);
+        assert!(Override::from_entry("informatik:bai3-ad|2025-06-02T08:15|unknown").is_err());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        // arrange
+        let events = vec![
+            event_at("AD", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+            event_at("AD", "2025-06-09 08:15:00", "2025-06-09 09:45:00"),
+            event_at("AD", "2025-06-16 08:15:00", "2025-06-16 09:45:00"),
+        ];
+        let entries = vec![
+            "informatik:bai3-ad|2025-06-02T08:15|remove".to_string(),
+            "informatik:bai3-ad|2025-06-09T08:15|rename|AD Klausur".to_string(),
+            "informatik:bai3-ad|2025-06-09T08:15|location|BT7-01.12".to_string(),
+            "informatik:bai3-ad|2025-06-16T08:15|time|2025-06-18T12:15|2025-06-18T13:45".to_string(),
+            "informatik:bai3-ad|2025-06-16T08:15|note|Laptop mitbringen".to_string(),
+            "informatik:bai3-bs|2025-06-09T08:15|remove".to_string(),
+        ];
+
+        // act
+        let result = overrides::apply(&meta("bai3-ad"), events, &overrides::from_entries(&entries));
+
+        // assert
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].name, "AD Klausur");
+        assert_eq!(result[0].location, "BT7-01.12");
+        assert_eq!(result[1].start, parse_datetime("2025-06-18 12:15:00"));
+        assert_eq!(result[1].end, parse_datetime("2025-06-18 13:45:00"));
+        assert_eq!(result[1].description, "Note: Laptop mitbringen");
+    }
+
+    #[test]
+    fn test_search_modules() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let eventdata_dir = temp_dir.path().join("eventdata");
+        for (department, module, name, description) in [
+            ("informatik", "bai3-ad", "BAI3-AD Algorithmen und Datenstrukturen", ""),
+            ("informatik", "bai3-bs", "BAI3-BS Betriebssysteme", "Prozesse und Threads"),
+            ("maschinenbau", "mb2-mathe", "Mathematik 2", "Lineare Algebra und Datenstrukturen der Numerik"),
+        ] {
+            fs::create_dir_all(eventdata_dir.join(department)).unwrap();
+            let mut event = event_at(name, "2025-06-02 08:15:00", "2025-06-02 09:45:00");
+            event.description = description.to_string();
+            fs::write(
+                eventdata_dir.join(department).join(format!("{}.json", module)),
+                serde_json::to_string(&vec![event]).unwrap(),
+            ).unwrap();
+        }
+
+        // act
+        let index = EventIndex::build(&eventdata_dir).unwrap();
+        let by_module = search::search(&index, "bai3ad").unwrap();
+        let by_typo = search::search(&index, "bai3-bd").unwrap();
+        let by_event = search::search(&index, "Datenstrukturen").unwrap();
+
+        // assert
+        assert_eq!(by_module[0].module, "informatik:bai3-ad");
+        assert_eq!(by_typo[0].module, "informatik:bai3-ad");
+        let modules: Vec<&str> = by_event.iter().map(|result| result.module.as_str()).collect();
+        assert_eq!(modules, vec!["informatik:bai3-ad", "maschinenbau:mb2-mathe"]);
+        assert_eq!(by_event[1].matched, "description of 'Mathematik 2'");
+        assert!(search::search(&index, "chemie").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(search::fuzzy_score("bai3-ad", "bai3-ad"), Some(100));
+        assert_eq!(search::fuzzy_score("bai3", "bai3-ad"), Some(90));
+        assert_eq!(search::fuzzy_score("ad", "bai3-ad"), Some(85));
+        assert_eq!(search::fuzzy_score("xyz", "bai3-ad"), None);
+    }
+
+    #[test]
+    fn test_find_free_rooms() {
+        // arrange
+        let mut events = [
+            event_at("AD", "2025-06-02 10:00:00", "2025-06-02 11:30:00"),
+            event_at("BS", "2025-06-02 12:15:00", "2025-06-02 13:45:00"),
+            event_at("SE", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+            event_at("Mathe", "2025-06-03 10:00:00", "2025-06-03 11:30:00"),
+            event_at("Online", "2025-06-02 10:00:00", "2025-06-02 11:30:00"),
+        ];
+        events[0].location = "BT7-01.12, BT7-01.13".to_string();
+        events[1].location = "BT7-01.14".to_string();
+        events[2].location = "bt7-01.12".to_string();
+        events[3].location = "BT70-1.01".to_string();
+        events[4].location = "Online".to_string();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let between = free_slots::parse_time_window("10:30-12:00").unwrap();
+
+        // act
+        let known_rooms: Vec<String> = events.iter().flat_map(|event| rooms::rooms_of(&event.location)).collect();
+        let events: Vec<&HawEventEntry> = events.iter().collect();
+        let result = rooms::find_free_rooms(&known_rooms, &events, date, between, Some("bt7"));
+
+        // assert
+        let free: Vec<(&str, Option<NaiveDateTime>)> =
+            result.iter().map(|room| (room.room.as_str(), room.free_until)).collect();
+        assert_eq!(free, vec![("BT7-01.14", Some(parse_datetime("2025-06-02 12:15:00")))]);
+        assert_eq!(rooms::find_free_rooms(&known_rooms, &events, date, between, None).len(), 2);
+    }
+
+    #[test]
+    fn test_room_schedule() {
+        // arrange
+        let mut events = [
+            event_at("AD", "2025-06-02 10:00:00", "2025-06-02 11:30:00"),
+            event_at("SE", "2025-06-02 08:15:00", "2025-06-02 09:45:00"),
+            event_at("BS", "2025-06-09 08:15:00", "2025-06-09 09:45:00"),
+        ];
+        events[0].location = "BT7-01.12 & BT7-01.13".to_string();
+        events[2].location = "BT7-01.13".to_string();
+        let (monday, sunday) = schedule::week_range(NaiveDate::from_ymd_opt(2025, 6, 4).unwrap(), 0);
+
+        // act
+        let index_events: Vec<&HawEventEntry> = events
+            .iter()
+            .filter(|event| rooms::rooms_of(&event.location).contains(&"BT7-01.13".to_string()))
+            .collect();
+        let result = rooms::room_schedule(&index_events, monday, sunday);
+
+        // assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "AD");
+    }
+
+    #[test]
+    fn test_event_index_keeps_unparsable_modules() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let eventdata_dir = create_test_eventdata(temp_dir.path());
+        fs::write(eventdata_dir.join("test-department").join("broken.json"), "[{\"name\": ").unwrap();
+        let broken = EventMeta {
+            department: "test-department".to_string(),
+            module: "broken".to_string(),
+        };
+
+        // act
+        let index = EventIndex::build(&eventdata_dir).unwrap();
+
+        // assert
+        assert_eq!(index.modules("test-department"), vec!["broken", "test-module"]);
+        assert!(index.is_corrupt(&broken));
+        assert!(!index.is_corrupt(&standard_event_meta()));
+        assert_eq!(index.module_events(&standard_event_meta()), Some(&[standard_event()][..]));
+    }
+
+    #[test]
+    fn test_event_index_queries() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let eventdata_dir = create_test_eventdata(temp_dir.path());
+        let mut shared = event_at("AD", "2025-06-02 10:00:00", "2025-06-02 11:30:00");
+        shared.location = "BT7-01.12".to_string();
+        for module in ["bai3-ad", "bai3-ad-wp"] {
+            fs::create_dir_all(eventdata_dir.join("informatik")).unwrap();
+            fs::write(
+                eventdata_dir.join("informatik").join(format!("{}.json", module)),
+                serde_json::to_string(&vec![shared.clone()]).unwrap(),
+            ).unwrap();
+        }
+
+        // act
+        let index = EventIndex::rebuild(temp_dir.path()).unwrap();
+        let stored = EventIndex::load(temp_dir.path()).unwrap();
+
+        // assert
+        assert_eq!(index.departments(), vec!["informatik", "test-department"]);
+        assert_eq!(index.modules("informatik"), vec!["bai3-ad", "bai3-ad-wp"]);
+        assert_eq!(index.module_events(&standard_event_meta()), Some(&[standard_event()][..]));
+        assert_eq!(index.module_events(&meta("unknown")), None);
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        assert_eq!(index.events_in_range(date, date), vec![&shared]);
+        assert_eq!(index.room_events("bt7-01.12"), vec![&shared]);
+        assert_eq!(stored, index);
+    }
+
+    #[test]
+    fn test_event_errors() {
+        // arrange
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let eventdata_dir = create_test_eventdata(temp_dir.path());
+        fs::write(eventdata_dir.join("timestamp"), chrono::Local::now().timestamp().to_string()).unwrap();
+        EventIndex::rebuild(temp_dir.path()).unwrap();
+        let boxed: Box<dyn std::error::Error> = EventError::AuthFailed("401 Unauthorized".to_string()).into();
+
+        // act
+        let missing = HawEventEntry::load_from_local(&meta("unknown"), temp_dir.path().to_path_buf());
+        let unboxed = EventError::from(boxed);
+        let corrupt = EventError::from(serde_json::from_str::<Vec<HawEventEntry>>("[").unwrap_err());
+
+        // assert
+        assert!(matches!(missing, Err(EventError::NotFound(_))), "Expected NotFound, got {:?}", missing);
+        assert!(matches!(unboxed, EventError::AuthFailed(_)));
+        assert_eq!(unboxed.exit_code(), 8);
+        assert_eq!(corrupt.exit_code(), 6);
+        assert_eq!(crate::cmd::exit_code(&corrupt), 6);
+        assert_eq!(crate::cmd::exit_code(&std::io::Error::other("other")), 1);
+    }
+
+    #[test]
+    fn test_mup_plans_are_indexed_as_modules() {
+        // arrange
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let eventdata_dir = create_test_eventdata(temp_dir.path());
+        let lecture: MupLecture = serde_json::from_value(serde_json::json!({
+            "name": "B_MT1-MAT",
+            "location": "BT7-01.12",
+            "description": "Professor: Abc, Discription: ",
+            "start": "2025-06-02T08:15:00",
+            "end": "2025-06-02T09:45:00",
+        }))
+        .unwrap();
+
+        // act
+        MupLecture::save_struct_to_json(vec![("B_MT1".to_string(), vec![lecture])], &eventdata_dir).unwrap();
+        let index = EventIndex::build(&eventdata_dir).unwrap();
+        let events = index.module_events(&EventMeta {
+            department: MUP_DEPARTMENT.to_string(),
+            module: "b-mt1".to_string(),
+        });
+
+        // assert
+        assert_eq!(index.modules(MUP_DEPARTMENT), vec!["b-mt1".to_string()]);
+        assert_eq!(events.map(|events| events[0].name.as_str()), Some("B_MT1-MAT"));
+        assert!(crate::cache::last_refresh(&eventdata_dir.join(MUP_DEPARTMENT)).is_some());
+    }
+
+    #[test]
+    fn test_mup_parser_matches_html_fixtures() {
+        // arrange
+        let fixture_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/mup");
+        let mut pages: Vec<PathBuf> = fs::read_dir(&fixture_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+            .collect();
+        pages.sort();
+
+        for page in pages {
+            let expected_path = page.with_extension("expected.json");
+            let expected: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(&expected_path).unwrap()).unwrap();
+
+            // act
+            let report = MupLecture::parse_html(&fs::read_to_string(&page).unwrap());
+
+            // assert
+            assert_eq!(serde_json::to_value(&report).unwrap(), expected, "{} differs from {}", page.display(), expected_path.display());
+        }
+    }
+
+    #[test]
+    fn test_mup_parse_report_repeats_cells_over_semester() {
+        // arrange
+        let body = fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/mup/b-mt1.html")).unwrap();
+        let semester = Semester::new(Term::Winter, 2025);
+
+        // act
+        let report = MupLecture::parse_html(&body);
+        let lectures: Vec<HawEventEntry> = report.lectures("B_MT1", &semester).into_iter().map(HawEventEntry::from).collect();
+
+        // assert
+        let wst: Vec<&HawEventEntry> = lectures.iter().filter(|lecture| lecture.name == "B_MT1-WST").collect();
+        let mat = lectures.iter().filter(|lecture| lecture.name == "B_MT1-MAT").count();
+        assert_eq!(wst[0].start, NaiveDate::from_ymd_opt(2025, 9, 26).unwrap().and_hms_opt(10, 0, 0).unwrap());
+        assert_eq!(wst[1].start, NaiveDate::from_ymd_opt(2025, 10, 10).unwrap().and_hms_opt(10, 0, 0).unwrap());
+        assert_eq!(mat, semester.lecture_dates(chrono::Weekday::Mon, WeekParity::Every).len());
+    }
+
+    #[test]
+    fn test_mup_groups_are_discovered_on_index_page() {
+        // arrange
+        let base_url = "https://www.mp.haw-hamburg.de/auth/vorlesungsplan/";
+        let body = fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data/mup/groups/index.html")).unwrap();
+
+        // act
+        let groups = MupLecture::parse_group_index(&body, base_url);
+
+        // assert
+        let ids: Vec<&str> = groups.iter().map(|group| group.id.as_str()).collect();
+        assert_eq!(ids, vec!["B_MT1", "B_MT2", "5_6DM", "Master_P"]);
+        assert_eq!(groups[0].name, "Mechatronik 1. Semester");
+        assert_eq!(groups[1].url, format!("{}B_MT2.php", base_url));
+        assert_eq!(groups[2].module(), "5-6dm");
+    }
+
+    /// Event source with fixed events, independent of the config and cache.
+    #[derive(Debug, PartialEq)]
+    struct FixedEvent(NaiveDateTime);
+
+    impl Event for FixedEvent {
+        fn get_start(&self) -> NaiveDateTime {
+            self.0
+        }
+
+        fn get_configured_modules() -> Result<Vec<EventMeta>, EventError> {
+            Ok(vec![standard_event_meta()])
+        }
+
+        fn get_all_events_for_date(date: NaiveDate) -> Result<Vec<Self>, EventError> {
+            Self::get_events_in_range(date, date)
+        }
+
+        fn get_events_for_module(_event: &EventMeta) -> Result<Vec<Self>, EventError> {
+            Ok([
+                "2024-10-20 10:00:00",
+                "2025-09-20 10:00:00",
+                "2025-10-13 10:00:00",
+                "2025-10-20 08:15:00",
+                "2025-10-27 10:00:00",
+                "2025-11-20 10:00:00",
+            ]
+            .into_iter()
+            .map(|start| FixedEvent(parse_datetime(start)))
+            .collect())
+        }
+
+        fn get_events_for_date(event_descriptor: Vec<EventMeta>, date: NaiveDate) -> Result<Vec<Self>, EventError> {
+            Self::get_module_events_in_range(&event_descriptor, date, date)
+        }
+
+        fn get_modules_for_department(_department: &str, _filter: Option<&str>) -> Result<Vec<String>, EventError> {
+            Ok(vec![])
+        }
+
+        fn get_departments() -> Result<Vec<String>, EventError> {
+            Ok(vec![])
+        }
+
+        fn load_from_local(_event: &EventMeta, _cache_dir: PathBuf) -> Result<Vec<Self>, EventError> {
+            Err(EventError::NotFound("Local data of fixed events".to_string()))
+        }
+
+        fn fetch_events_for_module(_event: &EventMeta, _department: &str) -> Result<Vec<Self>, EventError> {
+            Err(EventError::NotFound("Remote data of fixed events".to_string()))
+        }
+
+        fn fetch_event_data(_cache_dir: &PathBuf) -> Result<(), EventError> {
+            Err(EventError::NotFound("Remote data of fixed events".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_events_for_date_match_the_full_date() {
+        // arrange
+        let date = NaiveDate::from_ymd_opt(2025, 10, 20).unwrap();
+        let expected = vec![FixedEvent(parse_datetime("2025-10-20 08:15:00"))];
+
+        // act
+        let all_events = FixedEvent::get_all_events_for_date(date).unwrap();
+        let module_events = FixedEvent::get_events_for_date(vec![standard_event_meta()], date).unwrap();
+
+        // assert
+        assert_eq!(all_events, expected, "Events on the same day of another month or the same weekday must not match");
+        assert_eq!(module_events, expected);
+    }
+
     fn create_test_eventdata(test_path: &std::path::Path) -> PathBuf {
         let eventdata_dir = test_path.join("eventdata");
         let department_dir = eventdata_dir.join("test-department");
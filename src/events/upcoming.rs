@@ -0,0 +1,109 @@
+use std::fmt;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+
+use crate::events::haw_event::HawEventEntry;
+
+/// Number of days to look ahead for the next event.
+pub const LOOKAHEAD_DAYS: i64 = 30;
+
+/// Event that is running right now.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentEvent {
+    pub event: HawEventEntry,
+    /// Minutes until the event ends.
+    pub ends_in_minutes: i64,
+}
+
+/// Next event that has not started yet.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NextEvent {
+    pub event: HawEventEntry,
+    /// Minutes until the event starts.
+    pub starts_in_minutes: i64,
+}
+
+impl CurrentEvent {
+    /// Single line for shell prompts and status bars.
+    pub fn compact(&self) -> String {
+        format!(
+            "{} ({}) until {}, {} left",
+            self.event.name,
+            self.event.location,
+            self.event.end.format("%H:%M"),
+            countdown(self.ends_in_minutes)
+        )
+    }
+}
+
+impl NextEvent {
+    /// Single line for shell prompts and status bars.
+    pub fn compact(&self) -> String {
+        format!(
+            "{} ({}) in {}",
+            self.event.name,
+            self.event.location,
+            countdown(self.starts_in_minutes)
+        )
+    }
+}
+
+impl fmt::Display for CurrentEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.event)?;
+        write!(f, "Ends in {}", countdown(self.ends_in_minutes))
+    }
+}
+
+impl fmt::Display for NextEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.event)?;
+        write!(f, "Starts in {}", countdown(self.starts_in_minutes))
+    }
+}
+
+/// Returns all events running at `now`, in the order they are given.
+pub fn current(events: &[HawEventEntry], now: NaiveDateTime) -> Vec<CurrentEvent> {
+    events
+        .iter()
+        .filter(|event| event.start <= now && now < event.end)
+        .map(|event| CurrentEvent {
+            event: event.clone(),
+            ends_in_minutes: minutes_between(now, event.end),
+        })
+        .collect()
+}
+
+/// Returns the first event starting after `now`.
+pub fn next(events: &[HawEventEntry], now: NaiveDateTime) -> Option<NextEvent> {
+    events
+        .iter()
+        .filter(|event| event.start > now)
+        .min_by_key(|event| (event.start, event.end))
+        .map(|event| NextEvent {
+            event: event.clone(),
+            starts_in_minutes: minutes_between(now, event.start),
+        })
+}
+
+/// Formats minutes as a short countdown like "25min", "1h 05min" or "2d 3h".
+pub fn countdown(minutes: i64) -> String {
+    let duration = Duration::minutes(minutes);
+
+    if duration.num_days() > 0 {
+        format!("{}d {}h", duration.num_days(), duration.num_hours() % 24)
+    } else if duration.num_hours() > 0 {
+        format!("{}h {:02}min", duration.num_hours(), minutes % 60)
+    } else {
+        format!("{}min", minutes)
+    }
+}
+
+/// Whole minutes from `from` to `to`, rounded up so a running countdown never shows 0min.
+fn minutes_between(from: NaiveDateTime, to: NaiveDateTime) -> i64 {
+    let seconds = (to - from).num_seconds();
+    (seconds + 59) / 60
+}
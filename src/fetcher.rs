@@ -0,0 +1,550 @@
+use std::{
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use flate2::read::GzDecoder;
+use reqwest::{
+    blocking::Client,
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{cache, json_parser::Config};
+
+/// File within the data directory storing the validators of the downloaded archive.
+const VALIDATORS_FILE: &str = ".fetch.json";
+
+/// Set by the global `--offline` flag.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Forbids all network access for the rest of the process.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether nothing may be downloaded, set with `--offline` or `mensa settings offline on`.
+/// Local `file://` sources are still read.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed) || Config::load_config().get_offline()
+}
+
+/// Dataset that is cached locally, each with its own configurable source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    Mensa,
+    Events,
+}
+
+impl Provider {
+    pub fn from_str(s: &str) -> Option<Provider> {
+        match s.trim().to_lowercase().as_str() {
+            "mensa" => Some(Provider::Mensa),
+            "events" => Some(Provider::Events),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Provider::Mensa => "mensa",
+            Provider::Events => "events",
+        }
+    }
+
+    /// The upstream repository of the HAWHHCalendarBot project.
+    pub fn default_source(&self) -> Source {
+        match self {
+            Provider::Mensa => Source::github("HAWHHCalendarBot/mensa-data", "main"),
+            Provider::Events => Source::github("HAWHHCalendarBot/eventfiles", "main"),
+        }
+    }
+
+    /// Source configured for the provider, or the upstream repository.
+    /// Invalid configured sources are reported and ignored.
+    pub fn source(&self) -> Source {
+        let configured = Config::load_config()
+            .get_data_sources()
+            .into_iter()
+            .find(|(provider, _)| Provider::from_str(provider) == Some(*self));
+
+        match configured.map(|(_, url)| Source::parse(&url)) {
+            Some(Ok(source)) => source,
+            Some(Err(e)) => {
+                eprintln!("Ignoring {} data source: {}", self.as_str(), e);
+                self.default_source()
+            }
+            None => self.default_source(),
+        }
+    }
+}
+
+/// Where the cached data is downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Repository on a server, e.g. GitHub or a department mirror.
+    Remote {
+        git_url: String,
+        /// `.tar.gz` archive of the repository, all files are below a single top-level directory.
+        archive_url: String,
+        /// Base URL the single files of the repository are served under.
+        raw_url: String,
+    },
+    /// Local directory with the same layout as the repository.
+    Directory(PathBuf),
+}
+
+impl Source {
+    /// Source for the branch of a GitHub repository, e.g. `github("HAWHHCalendarBot/eventfiles", "main")`.
+    pub fn github(repository: &str, branch: &str) -> Source {
+        Source::Remote {
+            git_url: format!("https://github.com/{}.git", repository),
+            archive_url: format!("https://github.com/{}/archive/refs/heads/{}.tar.gz", repository, branch),
+            raw_url: format!("https://raw.githubusercontent.com/{}/refs/heads/{}/", repository, branch),
+        }
+    }
+
+    /// Parses a configured source:
+    /// - `file:///srv/eventfiles` for a local directory
+    /// - `http://mirror.example/eventfiles/` for a mirror serving the files and `archive.tar.gz`,
+    ///   the git backend clones the URL itself
+    /// - `github:HAWHHCalendarBot/eventfiles@main` for another GitHub repository or branch
+    pub fn parse(url: &str) -> Result<Source, String> {
+        let url = url.trim();
+
+        if let Some(path) = url.strip_prefix("file://") {
+            if path.is_empty() {
+                return Err(format!("'{}' has no path", url));
+            }
+            return Ok(Source::Directory(PathBuf::from(path)));
+        }
+
+        if let Some(repository) = url.strip_prefix("github:") {
+            let (repository, branch) = repository.split_once('@').unwrap_or((repository, "main"));
+            if repository.split('/').filter(|part| !part.is_empty()).count() != 2 {
+                return Err(format!("'{}' is not in the format github:owner/repository[@branch]", url));
+            }
+            return Ok(Source::github(repository, branch));
+        }
+
+        if url.starts_with("http://") || url.starts_with("https://") {
+            let base = url.trim_end_matches('/');
+            return Ok(Source::Remote {
+                git_url: base.to_string(),
+                archive_url: format!("{}/archive.tar.gz", base),
+                raw_url: format!("{}/", base),
+            });
+        }
+
+        Err(format!("'{}' is not a file://, http(s):// or github: source", url))
+    }
+
+    /// Fetches a single file of the repository, e.g. `informatik/bai3-ad.json`.
+    /// Returns `None` if the file does not exist.
+    pub fn fetch_file(&self, path: &str) -> Result<Option<String>, FetchError> {
+        match self {
+            Source::Directory(directory) => match fs::read_to_string(directory.join(path)) {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Source::Remote { .. } if is_offline() => Err(FetchError::Offline),
+            Source::Remote { raw_url, .. } => {
+                let response = reqwest::blocking::get(format!("{}{}", raw_url, path))?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                if !response.status().is_success() {
+                    return Err(FetchError::Failed(response.status().to_string()));
+                }
+                Ok(Some(response.text()?))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Remote { archive_url, git_url, .. } => write!(f, "{} (git: {})", archive_url, git_url),
+            Source::Directory(directory) => write!(f, "file://{}", directory.display()),
+        }
+    }
+}
+
+/// How the data is downloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Downloads the repository archive over HTTP, no `git` binary needed.
+    Archive,
+    /// Clones the repository with the `git` binary.
+    Git,
+}
+
+impl Backend {
+    pub fn from_str(s: &str) -> Option<Backend> {
+        match s.trim().to_lowercase().as_str() {
+            "archive" | "http" => Some(Backend::Archive),
+            "git" => Some(Backend::Git),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Backend::Archive => "archive",
+            Backend::Git => "git",
+        }
+    }
+
+    /// Backend from the config, downloading the archive by default.
+    pub fn from_config() -> Backend {
+        Config::load_config()
+            .get_fetch_backend()
+            .and_then(|backend| Backend::from_str(&backend))
+            .unwrap_or(Backend::Archive)
+    }
+}
+
+/// Result of a download.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Fetched {
+    /// New data was downloaded.
+    Updated,
+    /// The server reported the data as unchanged, the current data was copied instead.
+    NotModified,
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    Io(io::Error),
+    /// The download or clone failed.
+    Failed(String),
+    /// Nothing may be downloaded in offline mode.
+    Offline,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Io(e) => write!(f, "{}", e),
+            FetchError::Failed(reason) => write!(f, "{}", reason),
+            FetchError::Offline => write!(f, "offline mode"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> Self {
+        FetchError::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::Failed(e.to_string())
+    }
+}
+
+/// `ETag` and `Last-Modified` of the downloaded archive, sent back to only download changes.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Reads the validators stored with the data, missing or broken files count as no validators.
+    pub fn read(data_path: &Path) -> Validators {
+        fs::read_to_string(data_path.join(VALIDATORS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, data_path: &Path) -> io::Result<()> {
+        fs::write(data_path.join(VALIDATORS_FILE), serde_json::to_string(self)?)
+    }
+}
+
+/// Downloads the source into the staging directory (which must not exist yet).
+/// `current` is the data directory that is refreshed, it is copied if the archive is unchanged.
+/// Local directories are copied regardless of the backend.
+pub fn fetch(source: &Source, backend: Backend, current: &Path, staging: &Path) -> Result<Fetched, FetchError> {
+    match (source, backend) {
+        (Source::Directory(directory), _) => {
+            if !directory.is_dir() {
+                return Err(FetchError::Failed(format!("{} is not a directory", directory.display())));
+            }
+            cache::copy_dir(directory, staging)?;
+            Ok(Fetched::Updated)
+        }
+        (Source::Remote { .. }, _) if is_offline() => Err(FetchError::Offline),
+        (Source::Remote { archive_url, .. }, Backend::Archive) => fetch_archive(archive_url, current, staging),
+        (Source::Remote { git_url, .. }, Backend::Git) => {
+            clone(git_url, staging)?;
+            Ok(Fetched::Updated)
+        }
+    }
+}
+
+/// Clones the repository with the `git` binary.
+fn clone(git_url: &str, staging: &Path) -> Result<(), FetchError> {
+    let output = Command::new("git")
+        .arg("clone")
+        .arg("--depth")
+        .arg("1")
+        .arg(git_url)
+        .arg(staging)
+        .output()
+        .map_err(|e| FetchError::Failed(format!("could not run git: {}", e)))?;
+
+    if !output.status.success() {
+        let error_message = String::from_utf8_lossy(&output.stderr);
+        return Err(FetchError::Failed(format!("git clone failed: {}", error_message.trim())));
+    }
+    Ok(())
+}
+
+/// Downloads and extracts the archive with a conditional request, so unchanged data costs one round trip.
+fn fetch_archive(archive_url: &str, current: &Path, staging: &Path) -> Result<Fetched, FetchError> {
+    let mut request = Client::new().get(archive_url);
+
+    // Without current data the validators would skip the only download
+    if current.exists() {
+        let validators = Validators::read(current);
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send()?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        cache::copy_dir(current, staging)?;
+        return Ok(Fetched::NotModified);
+    }
+    if !response.status().is_success() {
+        return Err(FetchError::Failed(format!("{} returned {}", archive_url, response.status())));
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    extract_archive(response, staging)?;
+    validators.write(staging)?;
+    Ok(Fetched::Updated)
+}
+
+/// Extracts a `.tar.gz` archive into the directory, without its top-level directory.
+pub fn extract_archive(archive: impl io::Read, target: &Path) -> Result<(), FetchError> {
+    fs::create_dir_all(target)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(path) = strip_top_level(&entry.path()?) else {
+            continue;
+        };
+
+        let destination = target.join(path);
+        if entry.header().entry_type().is_dir() {
+            fs::create_dir_all(&destination)?;
+        } else if entry.header().entry_type().is_file() {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            entry.unpack(&destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the top-level directory of an archive path, rejecting paths that leave the archive.
+fn strip_top_level(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    components.next()?;
+
+    let rest: PathBuf = components.collect();
+    let inside = rest.components().all(|component| matches!(component, Component::Normal(_)));
+    (inside && !rest.as_os_str().is_empty()).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_parser::isolate_config;
+    use flate2::{write::GzEncoder, Compression};
+    use tempfile::tempdir;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_archive() {
+        let temp_dir = tempdir().unwrap();
+        let target = temp_dir.path().join("eventdata");
+        let data = archive(&[
+            ("eventfiles-main/informatik/bai3-ad.json", "[]"),
+            ("eventfiles-main/README.md", "readme"),
+        ]);
+
+        extract_archive(data.as_slice(), &target).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("informatik/bai3-ad.json")).unwrap(), "[]");
+        assert!(target.join("README.md").exists());
+        assert!(!target.join("eventfiles-main").exists());
+    }
+
+    #[test]
+    fn test_strip_top_level() {
+        assert_eq!(strip_top_level(Path::new("repo-main/a/b.json")), Some(PathBuf::from("a/b.json")));
+        assert_eq!(strip_top_level(Path::new("repo-main/")), None);
+        assert_eq!(strip_top_level(Path::new("repo-main/../escape")), None);
+    }
+
+    /// Serves the responses to the requests in order, like a department mirror.
+    fn serve(responses: Vec<(&'static str, Vec<u8>)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/eventfiles/", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = vec![];
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                requests.push(request);
+
+                let header = format!(
+                    "HTTP/1.1 {}\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_source_parse() {
+        assert_eq!(
+            Source::parse("file:///srv/eventfiles"),
+            Ok(Source::Directory(PathBuf::from("/srv/eventfiles")))
+        );
+        assert_eq!(
+            Source::parse("http://mirror.local/eventfiles/"),
+            Ok(Source::Remote {
+                git_url: "http://mirror.local/eventfiles".to_string(),
+                archive_url: "http://mirror.local/eventfiles/archive.tar.gz".to_string(),
+                raw_url: "http://mirror.local/eventfiles/".to_string(),
+            })
+        );
+        assert_eq!(
+            Source::parse("github:HAWHHCalendarBot/eventfiles@dev"),
+            Ok(Source::github("HAWHHCalendarBot/eventfiles", "dev"))
+        );
+        assert!(Source::parse("github:eventfiles").is_err());
+        assert!(Source::parse("ftp://mirror.local").is_err());
+    }
+
+    #[test]
+    fn test_fetch_from_directory() {
+        let temp_dir = tempdir().unwrap();
+        let mirror = temp_dir.path().join("mirror");
+        fs::create_dir_all(mirror.join("informatik")).unwrap();
+        fs::write(mirror.join("informatik/bai3-ad.json"), "[]").unwrap();
+        let source = Source::parse(&format!("file://{}", mirror.display())).unwrap();
+        let staging = temp_dir.path().join("eventdata.staging");
+
+        let fetched = fetch(&source, Backend::Git, &temp_dir.path().join("eventdata"), &staging).unwrap();
+
+        assert_eq!(fetched, Fetched::Updated);
+        assert!(staging.join("informatik/bai3-ad.json").exists());
+        assert_eq!(source.fetch_file("informatik/bai3-ad.json").unwrap(), Some("[]".to_string()));
+        assert_eq!(source.fetch_file("informatik/unknown.json").unwrap(), None);
+    }
+
+    #[test]
+    fn test_fetch_from_mirror() {
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let current = temp_dir.path().join("eventdata");
+        let data = archive(&[("eventfiles/informatik/bai3-ad.json", "[]")]);
+        let (url, server) = serve(vec![
+            ("200 OK", data),
+            ("304 Not Modified", vec![]),
+            ("200 OK", b"[]".to_vec()),
+            ("404 Not Found", vec![]),
+        ]);
+        let source = Source::parse(&url).unwrap();
+        let staging = cache::staging_dir(&current);
+
+        let first = fetch(&source, Backend::Archive, &current, &staging).unwrap();
+        fs::rename(&staging, &current).unwrap();
+        let second = fetch(&source, Backend::Archive, &current, &staging).unwrap();
+        let file = source.fetch_file("informatik/bai3-ad.json").unwrap();
+        let missing = source.fetch_file("informatik/unknown.json").unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(first, Fetched::Updated);
+        assert_eq!(second, Fetched::NotModified);
+        assert!(staging.join("informatik/bai3-ad.json").exists());
+        assert!(requests[0].starts_with("GET /eventfiles/archive.tar.gz"));
+        assert!(requests[1].to_lowercase().contains("if-none-match: \"v1\""));
+        assert_eq!(file, Some("[]".to_string()));
+        assert_eq!(missing, None);
+    }
+    #[test]
+    fn test_validators_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let validators = Validators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+
+        validators.write(temp_dir.path()).unwrap();
+
+        assert_eq!(Validators::read(temp_dir.path()), validators);
+        assert_eq!(Validators::read(&temp_dir.path().join("missing")), Validators::default());
+        assert_eq!(Backend::from_str("GIT"), Some(Backend::Git));
+    }
+}
@@ -1,4 +1,6 @@
+use chrono::NaiveDate;
 use std::{clone, fmt::{format, Error}, fs::{self, File}, vec};
+use std::{path::PathBuf, sync::OnceLock};
 
 #[derive(Debug)]
 pub(crate) enum Occupations {
@@ -59,6 +61,15 @@ pub(crate) enum ConfigName {
     events,
     vusername,
     vpassword,
+    ics_sources,
+    event_overrides,
+    fetch_backend,
+    data_sources,
+    cache_ttl,
+    offline,
+    semester_dates,
+    password_command,
+    mup_groups,
 }
 
 
@@ -71,6 +82,15 @@ pub struct Config {
     events: Option<Vec<String>>,
     vusername: Option<String>,
     vpassword: Option<String>,
+    ics_sources: Option<Vec<String>>,
+    event_overrides: Option<Vec<String>>,
+    fetch_backend: Option<String>,
+    data_sources: Option<Vec<String>>,
+    cache_ttl: Option<Vec<String>>,
+    offline: Option<bool>,
+    semester_dates: Option<String>,
+    password_command: Option<String>,
+    mup_groups: Option<Vec<String>>,
 }
 
 impl clone::Clone for Config {
@@ -83,6 +103,15 @@ impl clone::Clone for Config {
             events: self.events.clone(),
             vusername: self.vusername.clone(),
             vpassword: self.vpassword.clone(),
+            ics_sources: self.ics_sources.clone(),
+            event_overrides: self.event_overrides.clone(),
+            fetch_backend: self.fetch_backend.clone(),
+            data_sources: self.data_sources.clone(),
+            cache_ttl: self.cache_ttl.clone(),
+            offline: self.offline,
+            semester_dates: self.semester_dates.clone(),
+            password_command: self.password_command.clone(),
+            mup_groups: self.mup_groups.clone(),
         }
     }
 }
@@ -103,7 +132,25 @@ impl Config {
             events: Some(Vec::new()),
             //Login V-Kennung:
             vusername: Some(String::new()),
-            vpassword: Some(String::new()),
+            vpassword: None,
+            //ICS Quellen (name:pfad):
+            ics_sources: Some(Vec::new()),
+            //Änderungen einzelner Termine:
+            event_overrides: Some(Vec::new()),
+            //Download über Archiv oder git:
+            fetch_backend: None,
+            //Datenquellen (provider=url):
+            data_sources: Some(Vec::new()),
+            //Gültigkeitsdauer der Caches (dataset=dauer):
+            cache_ttl: Some(Vec::new()),
+            //Offline Modus:
+            offline: None,
+            //Vorlesungszeit (start..ende):
+            semester_dates: None,
+            //Befehl, der das MuP Passwort ausgibt (z.B. pass):
+            password_command: None,
+            //Abonnierte MuP Semestergruppen (leer = alle):
+            mup_groups: Some(Vec::new()),
         }
 
     }
@@ -167,8 +214,53 @@ impl Config {
         self.vusername.clone()
     }
 
-    pub fn update_password(&mut self, password: String) {
-        self.vpassword = Some(password);
+    /// Password of an old config, new passwords are only kept in the credential store
+    pub fn get_cleartext_password(&self) -> Option<String> {
+        self.vpassword.clone().filter(|password| !password.is_empty())
+    }
+
+    pub fn remove_cleartext_password(&mut self) {
+        self.vpassword = None;
+    }
+
+    pub fn update_password_command(&mut self, command: &str) {
+        self.password_command = Some(command.to_string());
+    }
+
+    pub fn remove_password_command(&mut self) {
+        self.password_command = None;
+    }
+
+    pub fn get_password_command(&self) -> Option<String> {
+        self.password_command.clone()
+    }
+
+    /// Subscribe to the MuP semester group, stored as module name
+    pub fn add_mup_group(&mut self, module: &str) -> Result<(), String> {
+        let mup_groups = self.mup_groups.get_or_insert_with(Vec::new);
+        if mup_groups.iter().any(|e| e == module) {
+            return Err(format!("Already subscribed to '{}'.", module));
+        }
+
+        mup_groups.push(module.to_string());
+        Ok(())
+    }
+
+    /// Unsubscribe from the MuP semester group
+    pub fn remove_mup_group(&mut self, module: &str) -> Result<(), String> {
+        let mup_groups = self.mup_groups.get_or_insert_with(Vec::new);
+        let len_before = mup_groups.len();
+        mup_groups.retain(|e| e != module);
+
+        if mup_groups.len() == len_before {
+            return Err(format!("Not subscribed to '{}'.", module));
+        }
+        Ok(())
+    }
+
+    /// Get the subscribed MuP semester groups, all groups are fetched if empty
+    pub fn get_mup_groups(&self) -> Vec<String> {
+        self.mup_groups.clone().unwrap_or_default()
     }
 
     /// Add module for events
@@ -206,14 +298,148 @@ impl Config {
         self.events.as_ref()
     }
 
-    pub fn get_password(&self) -> Option<String>{
-        self.vpassword.clone()
+    /// Add ics file as event source, the path is escaped so any file name survives saving the config
+    pub fn add_ics_source(&mut self, name: &str, path: &str) -> Result<(), String> {
+        if name.is_empty() || name.contains(':') || name.contains(RESERVED) {
+            return Err(format!("Invalid ICS source name '{}'", name));
+        }
+
+        let ics_sources = self.ics_sources.get_or_insert_with(Vec::new);
+        if ics_sources.iter().any(|e| e.split_once(':').map(|(n, _)| n) == Some(name)) {
+            return Err(format!("ICS source '{}' already exists.", name));
+        }
+
+        ics_sources.push(format!("{}:{}", name, escape(path)));
+        Ok(())
+    }
+
+    /// Remove ics file as event source
+    pub fn remove_ics_source(&mut self, name: &str) -> Result<(), String> {
+        let ics_sources = self.ics_sources.get_or_insert_with(Vec::new);
+        let len_before = ics_sources.len();
+        ics_sources.retain(|e| e.split_once(':').map(|(n, _)| n) != Some(name));
+
+        if ics_sources.len() == len_before {
+            return Err(format!("ICS source '{}' does not exist.", name));
+        }
+        Ok(())
+    }
+
+    /// Get all ics sources as (name, path) pairs
+    pub fn get_ics_sources(&self) -> Vec<(String, String)> {
+        self.ics_sources
+            .iter()
+            .flatten()
+            .filter_map(|e| e.split_once(':'))
+            // Sources of older configs were saved without escaping
+            .map(|(name, path)| (name.to_string(), unescape(path).unwrap_or_else(|| path.to_string())))
+            .collect()
+    }
+
+    /// Set override for a single event occurrence, replacing the override with the same key
+    pub fn set_event_override(&mut self, key: &str, entry: String) {
+        let event_overrides = self.event_overrides.get_or_insert_with(Vec::new);
+        event_overrides.retain(|e| e != key && !e.starts_with(&format!("{}|", key)));
+        event_overrides.push(entry);
+    }
+
+    /// Remove all overrides starting with the given key
+    pub fn remove_event_overrides(&mut self, key: &str) -> Result<(), String> {
+        let event_overrides = self.event_overrides.get_or_insert_with(Vec::new);
+        let len_before = event_overrides.len();
+        event_overrides.retain(|e| !e.starts_with(key));
+
+        if event_overrides.len() == len_before {
+            return Err("No overrides found for this occurrence.".to_string());
+        }
+        Ok(())
+    }
+
+    /// Get all event overrides as stored in the config
+    pub fn get_event_overrides(&self) -> Vec<String> {
+        self.event_overrides.clone().unwrap_or_default()
+    }
+
+    pub fn update_fetch_backend(&mut self, backend: &str) {
+        self.fetch_backend = Some(backend.to_string());
+    }
+
+    pub fn get_fetch_backend(&self) -> Option<String> {
+        self.fetch_backend.clone()
+    }
+
+    pub fn update_offline(&mut self, offline: bool) {
+        self.offline = Some(offline);
+    }
+
+    pub fn get_offline(&self) -> bool {
+        self.offline.unwrap_or(false)
+    }
+
+    pub fn update_semester_dates(&mut self, start: NaiveDate, end: NaiveDate) {
+        self.semester_dates = Some(format!("{}..{}", start, end));
+    }
+
+    pub fn remove_semester_dates(&mut self) {
+        self.semester_dates = None;
+    }
+
+    pub fn get_semester_dates(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let (start, end) = self.semester_dates.as_ref()?.split_once("..")?;
+        Some((start.parse().ok()?, end.parse().ok()?))
+    }
+
+    pub fn set_data_source(&mut self, provider: &str, url: &str) {
+        let data_sources = self.data_sources.get_or_insert_with(Vec::new);
+        data_sources.retain(|e| e.split_once('=').map(|(p, _)| p) != Some(provider));
+        data_sources.push(format!("{}={}", provider, url));
+    }
+
+    pub fn remove_data_source(&mut self, provider: &str) -> Result<(), String> {
+        let data_sources = self.data_sources.get_or_insert_with(Vec::new);
+        let len_before = data_sources.len();
+        data_sources.retain(|e| e.split_once('=').map(|(p, _)| p) != Some(provider));
+
+        if data_sources.len() == len_before {
+            return Err(format!("No data source configured for '{}'", provider));
+        }
+        Ok(())
+    }
+
+    pub fn set_cache_ttl(&mut self, dataset: &str, ttl: &str) {
+        let cache_ttl = self.cache_ttl.get_or_insert_with(Vec::new);
+        cache_ttl.retain(|e| e.split_once('=').map(|(d, _)| d) != Some(dataset));
+        cache_ttl.push(format!("{}={}", dataset, ttl));
+    }
+
+    pub fn get_cache_ttls(&self) -> Vec<(String, String)> {
+        self.cache_ttl
+            .iter()
+            .flatten()
+            .filter_map(|e| e.split_once('='))
+            .map(|(dataset, ttl)| (dataset.to_string(), ttl.to_string()))
+            .collect()
+    }
+
+    pub fn get_data_sources(&self) -> Vec<(String, String)> {
+        self.data_sources
+            .iter()
+            .flatten()
+            .filter_map(|e| e.split_once('='))
+            .map(|(provider, url)| (provider.to_string(), url.to_string()))
+            .collect()
+    }
+
+    /// Directory of `cfg.json` and the credential store.
+    pub fn config_dir() -> PathBuf {
+        CONFIG_DIR
+            .get()
+            .cloned()
+            .unwrap_or_else(|| dirs::config_local_dir().unwrap().join("hawhhcalendarbot-cli"))
     }
 
     pub fn load_config() -> Config {
-        let path = dirs::config_local_dir()
-                .unwrap()
-                .join("hawhhcalendarbot-cli/cfg.json");
+        let path = Self::config_dir().join("cfg.json");
         match fs::read_to_string(path,
         ) {
             Ok(json_config) => Config::struct_from_json_file(&json_config).expect("Fehler beim Parsen der JSON"),
@@ -223,15 +449,13 @@ impl Config {
     }
 
     pub fn save_config_json(user_config: &Config) {
-        let conf_dir = dirs::config_local_dir().unwrap().join("hawhhcalendarbot-cli");
+        let conf_dir = Self::config_dir();
 
         let json_string = Config::json_file_from_struct(user_config).expect("Fehler beim Serialisieren");
-        let _ = fs::create_dir_all(conf_dir);
+        let _ = fs::create_dir_all(&conf_dir);
 
         fs::write(
-            dirs::config_local_dir()
-                .unwrap()
-                .join("hawhhcalendarbot-cli/cfg.json"),
+            conf_dir.join("cfg.json"),
         json_string,
         )
         .expect("Fehler beim Schreiben der Datei");
@@ -246,6 +470,55 @@ impl Config {
         }
     }   
 
+    //Optionale Liste extrahieren, ältere Configs enthalten das Feld nicht:
+    fn extract_optional_list(content: &str, key: &str) -> Vec<String> {
+        let key_pattern = format!("\"{}\"", key);
+        let Some(key_idx) = content.find(&key_pattern) else {
+            return Vec::new();
+        };
+
+        let slice = &content[key_idx + key_pattern.len()..];
+        let (Some(start), Some(end)) = (slice.find('['), slice.find(']')) else {
+            return Vec::new();
+        };
+        if start > end {
+            return Vec::new();
+        }
+
+        slice[start + 1..end]
+            .split(',')
+            .map(|s| s.trim().trim_matches('"').to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    //Optionalen Wert extrahieren, fehlt er oder ist er null, wird None zurückgegeben:
+    fn extract_optional_value(content: &str, key: &str) -> Option<String> {
+        let key_pattern = format!("\"{}\"", key);
+        let key_idx = content.find(&key_pattern)?;
+
+        let slice = content[key_idx + key_pattern.len()..].trim_start().strip_prefix(':')?.trim_start();
+        let value = slice.strip_prefix('"')?;
+        let end = value.find('"')?;
+
+        Some(value[..end].to_string()).filter(|value| !value.is_empty())
+    }
+
+    //Optionalen Wahrheitswert extrahieren, fehlt er oder ist er null, wird None zurückgegeben:
+    fn extract_optional_bool(content: &str, key: &str) -> Option<bool> {
+        let key_pattern = format!("\"{}\"", key);
+        let key_idx = content.find(&key_pattern)?;
+
+        let slice = content[key_idx + key_pattern.len()..].trim_start().strip_prefix(':')?.trim_start();
+        if slice.starts_with("true") {
+            Some(true)
+        } else if slice.starts_with("false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
 //Json Parser
     fn struct_from_json_file(/*path: &str*/ json_config: &String) -> Result<Config, Box<dyn std::error::Error>> {
         let search_offset: usize = 4;
@@ -296,11 +569,6 @@ impl Config {
             None => return Err("vusername nicht gefunden".into()),
         };
 
-        let up_end = match config_content_cleaned.find(ConfigName::vpassword.as_str()) {
-            Some(idx) => idx + ConfigName::vpassword.as_str().len(),
-            None => return Err("vpassword nicht gefunden".into()),
-        };
-
 
         //Inhalt der primary mensa extrahieren:
 
@@ -399,16 +667,37 @@ impl Config {
             None => "null".to_string(), // oder "" wenn du leeren String willst
         };
 
-        let slice = &config_content_cleaned[up_end + search_offset..];
-        let up = slice.find('"').map(|end| {
-            slice[..end].to_string()
-        });
+        //Passwort im Klartext (ältere Configs) extrahieren, wird in den Credential Store migriert:
+        let password = Self::extract_optional_value(&config_content_cleaned, ConfigName::vpassword.as_str());
 
-        let password = match up {
-            Some(up_str) => format!("{}", up_str),
-            None => "null".to_string(), // oder "" wenn du leeren String willst
-        };
 
+        //Inhalte der ICS Quellen extrahieren:
+        let ics_sources = Self::extract_optional_list(&config_content_cleaned, ConfigName::ics_sources.as_str());
+
+        //Inhalte der Terminänderungen extrahieren:
+        let event_overrides = Self::extract_optional_list(&config_content_cleaned, ConfigName::event_overrides.as_str());
+
+        //Download Backend extrahieren:
+        let fetch_backend = Self::extract_optional_value(&config_content_cleaned, ConfigName::fetch_backend.as_str());
+
+        //Datenquellen extrahieren:
+        let data_sources = Self::extract_optional_list(&config_content_cleaned, ConfigName::data_sources.as_str());
+
+        //Cache Gültigkeitsdauern extrahieren:
+        let cache_ttl = Self::extract_optional_list(&config_content_cleaned, ConfigName::cache_ttl.as_str());
+
+        //Offline Modus extrahieren:
+        let offline = Self::extract_optional_bool(&config_content_cleaned, ConfigName::offline.as_str());
+
+        //Vorlesungszeit extrahieren:
+        let semester_dates = Self::extract_optional_value(&config_content_cleaned, ConfigName::semester_dates.as_str());
+
+        //Passwort Befehl extrahieren:
+        let password_command = Self::extract_optional_value(&config_content_cleaned, ConfigName::password_command.as_str())
+            .and_then(|command| unescape(&command));
+
+        //Abonnierte MuP Semestergruppen extrahieren:
+        let mup_groups = Self::extract_optional_list(&config_content_cleaned, ConfigName::mup_groups.as_str());
 
         //Config zurückkgeben:
         Ok(Config { primary_mensa: Some(primary_mensa),
@@ -417,7 +706,16 @@ impl Config {
                     extras: Some(extra_list),
                     events: Some(event_list),
                     vusername: Some(username),
-                    vpassword: Some(password)
+                    vpassword: password,
+                    ics_sources: Some(ics_sources),
+                    event_overrides: Some(event_overrides),
+                    fetch_backend,
+                    data_sources: Some(data_sources),
+                    cache_ttl: Some(cache_ttl),
+                    offline,
+                    semester_dates,
+                    password_command,
+                    mup_groups: Some(mup_groups),
                 })
 
     }
@@ -470,7 +768,62 @@ impl Config {
             None => "null".to_string(), // oder "" falls du leere Strings willst
         };
 
-        let json_string = format!("{{ \n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {}\n}}", ConfigName::primary_mensa.as_str(), primary_mensa, ConfigName::mensa_list.as_str(), mensa_list, ConfigName::occupation.as_str(), occupations, ConfigName::extras.as_str(), extra_list, ConfigName::events.as_str(), event_list, ConfigName::vusername.as_str(), username, ConfigName::vpassword.as_str(), password);
+        let ics_list = format!("[{}]", config.ics_sources
+                                                        .iter()
+                                                        .flatten()
+                                                        .map(|s|  format!("{:?}", s))
+                                                        .collect::<Vec<String>>()
+                                                        .join(", "));
+
+        let override_list = format!("[{}]", config.event_overrides
+                                                        .iter()
+                                                        .flatten()
+                                                        .map(|s|  format!("{:?}", s))
+                                                        .collect::<Vec<String>>()
+                                                        .join(", "));
+
+        let fetch_backend = match &config.fetch_backend {
+            Some(backend) => format!("\"{}\"", backend),
+            None => "null".to_string(),
+        };
+
+        let source_list = format!("[{}]", config.data_sources
+                                                        .iter()
+                                                        .flatten()
+                                                        .map(|s|  format!("{:?}", s))
+                                                        .collect::<Vec<String>>()
+                                                        .join(", "));
+
+        let ttl_list = format!("[{}]", config.cache_ttl
+                                                        .iter()
+                                                        .flatten()
+                                                        .map(|s|  format!("{:?}", s))
+                                                        .collect::<Vec<String>>()
+                                                        .join(", "));
+
+        let offline = match config.offline {
+            Some(offline) => offline.to_string(),
+            None => "null".to_string(),
+        };
+
+        let semester_dates = match &config.semester_dates {
+            Some(dates) => format!("\"{}\"", dates),
+            None => "null".to_string(),
+        };
+
+        let password_command = match &config.password_command {
+            Some(command) => format!("\"{}\"", escape(command)),
+            None => "null".to_string(),
+        };
+
+        let group_list = format!("[{}]", config.mup_groups
+                                                        .iter()
+                                                        .flatten()
+                                                        .map(|s|  format!("{:?}", s))
+                                                        .collect::<Vec<String>>()
+                                                        .join(", "));
+
+        let json_string = format!("{{ \n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {},\n   \"{}\": {}\n}}", ConfigName::primary_mensa.as_str(), primary_mensa, ConfigName::mensa_list.as_str(), mensa_list, ConfigName::occupation.as_str(), occupations, ConfigName::extras.as_str(), extra_list, ConfigName::events.as_str(), event_list, ConfigName::vusername.as_str(), username, ConfigName::vpassword.as_str(), password, ConfigName::ics_sources.as_str(), ics_list, ConfigName::event_overrides.as_str(), override_list, ConfigName::fetch_backend.as_str(), fetch_backend, ConfigName::data_sources.as_str(), source_list, ConfigName::cache_ttl.as_str(), ttl_list, ConfigName::offline.as_str(), offline, ConfigName::semester_dates.as_str(), semester_dates, ConfigName::password_command.as_str(), password_command, ConfigName::mup_groups.as_str(), group_list);
 
         //fs::write(path, json_string)?;
 
@@ -480,6 +833,47 @@ impl Config {
     }
 
 }
+/// Replaces the config directory for the whole process, see `isolate_config`.
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Uses an empty config directory for the rest of the process,
+/// so tests do not depend on the settings of the developer.
+#[cfg(test)]
+pub(crate) fn isolate_config() {
+    CONFIG_DIR.get_or_init(|| tempfile::tempdir().expect("temporary config directory").keep());
+}
+
+/// Characters that would break a config entry, a config list or a config string.
+const RESERVED: [char; 8] = ['%', '|', ',', '"', '[', ']', '\\', '\n'];
+
+/// Percent-encodes the reserved characters, so a value survives saving and loading the config.
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            c if RESERVED.contains(&c) => format!("%{:02X}", c as u8),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Decodes a value encoded with `escape`, `None` if an escape sequence is invalid.
+pub(crate) fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            let hex: String = chars.by_ref().take(2).collect();
+            unescaped.push(char::from(u8::from_str_radix(&hex, 16).ok()?));
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    Some(unescaped)
+}
+
 /*
     Um an die Namen der Enums zu kommen, wird diese als impl implementiert, dies gibt den Namen als &str aus.
 */
@@ -539,6 +933,15 @@ impl ConfigName {
             ConfigName::events => "events",
             ConfigName::vusername => "vusername",
             ConfigName::vpassword => "vpassword",
+            ConfigName::ics_sources => "ics_sources",
+            ConfigName::event_overrides => "event_overrides",
+            ConfigName::fetch_backend => "fetch_backend",
+            ConfigName::data_sources => "data_sources",
+            ConfigName::cache_ttl => "cache_ttl",
+            ConfigName::offline => "offline",
+            ConfigName::semester_dates => "semester_dates",
+            ConfigName::password_command => "password_command",
+            ConfigName::mup_groups => "mup_groups",
         }
     }
 }
@@ -562,3 +965,37 @@ impl Occupations {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_command_roundtrip() {
+        let mut config = Config::new();
+        config.update_password_command(r#"pass show "mup/login" | sed 's/\\n//'"#);
+
+        let json = Config::json_file_from_struct(&config).unwrap();
+        let loaded = Config::struct_from_json_file(&json).unwrap();
+
+        assert_eq!(loaded.get_password_command(), config.get_password_command());
+    }
+
+    #[test]
+    fn test_ics_source_roundtrip() {
+        let mut config = Config::new();
+        config.add_ics_source("uni", r"C:\Kalender\Uni, Sport [2025].ics").unwrap();
+        config.add_ics_source("sport", "/home/user/sport.ics").unwrap();
+        assert!(config.add_ics_source("a,b", "/tmp/a.ics").is_err());
+
+        let json = Config::json_file_from_struct(&config).unwrap();
+        let loaded = Config::struct_from_json_file(&json).unwrap();
+
+        assert_eq!(
+            loaded.get_ics_sources(),
+            vec![
+                ("uni".to_string(), r"C:\Kalender\Uni, Sport [2025].ics".to_string()),
+                ("sport".to_string(), "/home/user/sport.ics".to_string()),
+            ]
+        );
+    }
+}
@@ -3,6 +3,7 @@
  *
  * calendarbot --help / calendarbot help
  * calendarbot --version / calendarbot version
+ * calendarbot --offline <command>: only uses the cached data, never downloads anything
  * ## Mensa
  * calendarbot mensa: shows the mensa menu for today
  * calendarbot mensa tomorrow: shows the mensa menu for tomorrow
@@ -17,12 +18,29 @@
  *    - occupation: sets the occupation (student, employee, guest)
  *    - extras: sets the extras (vegan, vegetarian, lactose-free, no alcohol, no beef, no fish...)
  *    - show ingredients: shows the ingredients when showing the menu
+ *    - offline: only uses the cached data (on, off)
+ *    - semester: sets the lecture period (start end)
+ *    - password: stores the MuP password encrypted (--command <cmd>, --show, --reset)
  * ## Events
  * calendarbot events: shows the selected events
  * calendarbot events list: lists all available events
  * calendarbot events list --filter <filter>: lists all available events with the given filter
  * calendarbot events add <event>: adds the event to the calendar
  * calendarbot events remove <event>: removes the event from the calendar
+ * ## Rooms
+ * calendarbot rooms free 2025-07-04 10:00-12:00 --building BT7: lists rooms without events in the time window
+ * calendarbot rooms schedule BT7-01.12 --week: shows the events in a room
+ * ## Serve
+ * calendarbot serve --port 8080: serves /calendar.ics and /mensa/<name>/<date>.json locally
+ * ## Cache
+ * calendarbot cache status: shows age, size and version of the cached data
+ * calendarbot cache clear [mensa|events|mup]: removes cached data
+ * calendarbot cache prune --older-than 7d: removes old cached data and snapshots
+ * calendarbot cache ttl <dataset> <ttl>: sets after which age data is refreshed
+ * ## MuP
+ * calendarbot mup parse <file.html>: parses a saved plan page and reports unparsable cells
+ * calendarbot mup groups: lists the semester groups found on the plan index page
+ * calendarbot mup subscribe <group> / unsubscribe <group>: only fetches the plans of the subscribed groups
  * 
  * 
 */
@@ -32,14 +50,19 @@ mod mensa;
 mod events;
 mod cmd;
 mod json_parser;
+mod server;
+mod cache;
+mod fetcher;
+mod semester;
+mod credentials;
 
-use crate::cmd::Cli;
+use crate::cmd::{exit_code, Cli};
 
 use clap::{Parser};
 
 fn main() {
     if let Err(e) = Cli::parse().run() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        std::process::exit(exit_code(e.as_ref()));
     }
 }
@@ -0,0 +1,115 @@
+use std::{error::Error, fmt};
+
+use chrono::NaiveDate;
+
+use crate::{fetcher::FetchError, semester};
+
+/// Errors of the mensa subsystem.
+#[derive(Debug)]
+pub enum MealError {
+    /// The mensa or the local mensa data does not exist.
+    NotFound(String),
+    /// The mensa has no menu for the date.
+    NoDataForDate { mensa: String, date: NaiveDate },
+    /// The local mensa data could not be read.
+    CacheCorrupt(String),
+    /// The mensa data could not be downloaded.
+    FetchFailed(String),
+    /// The mensa data is not cached and nothing may be downloaded in offline mode.
+    Offline,
+    Io(std::io::Error),
+    Other(String),
+}
+
+impl MealError {
+    /// Exit code of the CLI for this error, matching the codes of `EventError`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            MealError::NotFound(_) => 3,
+            MealError::NoDataForDate { .. } => 4,
+            MealError::CacheCorrupt(_) => 6,
+            MealError::FetchFailed(_) => 7,
+            MealError::Offline => 9,
+            MealError::Io(_) | MealError::Other(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for MealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MealError::NotFound(what) => write!(f, "{} not found", what),
+            MealError::NoDataForDate { mensa, date } => match semester::holiday(*date) {
+                Some(holiday) => write!(
+                    f,
+                    "Mensa '{}' is closed on {}: public holiday ({})",
+                    mensa, date, holiday.name
+                ),
+                None => write!(
+                    f,
+                    "No menu found for mensa '{}' on {}. The mensa might be closed (weekend or holiday)",
+                    mensa, date
+                ),
+            },
+            MealError::CacheCorrupt(reason) => write!(
+                f,
+                "Local mensa data is corrupt ({}). It is downloaded again on the next update",
+                reason
+            ),
+            MealError::FetchFailed(reason) => write!(
+                f,
+                "Could not download mensa data ({}). Check your network connection",
+                reason
+            ),
+            MealError::Offline => write!(
+                f,
+                "Mensa data is not available offline. Run once without --offline to download it"
+            ),
+            MealError::Io(e) => write!(f, "{}", e),
+            MealError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for MealError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MealError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MealError {
+    fn from(e: std::io::Error) -> Self {
+        MealError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for MealError {
+    fn from(e: serde_json::Error) -> Self {
+        MealError::CacheCorrupt(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for MealError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        MealError::CacheCorrupt(e.to_string())
+    }
+}
+
+impl From<FetchError> for MealError {
+    fn from(e: FetchError) -> Self {
+        match e {
+            FetchError::Io(e) => MealError::Io(e),
+            FetchError::Failed(reason) => MealError::FetchFailed(reason),
+            FetchError::Offline => MealError::Offline,
+        }
+    }
+}
+
+impl From<reqwest::Error> for MealError {
+    fn from(e: reqwest::Error) -> Self {
+        MealError::FetchFailed(e.to_string())
+    }
+}
@@ -1,13 +1,10 @@
-use std::{collections::BTreeMap, fmt, fs::{self, File}, io::{Read, Write}, path::{Path, PathBuf}, process::Command};
+use std::{collections::BTreeMap, fmt, fs, path::{Path, PathBuf}};
 
 use chrono::NaiveDate;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use reqwest::blocking as reqwest;
 
-use crate::{json_parser::Occupations, mensa::meal::{Contents, Meal, Prices}};
-
-const DATA_URL:&str = "https://raw.githubusercontent.com/HAWHHCalendarBot/mensa-data/main";
+use crate::{cache::{self, Dataset, Freshness}, fetcher::{self, Backend, Fetched, Provider}, json_parser::Occupations, mensa::{error::MealError, meal::{Contents, Meal, Prices}}};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -53,28 +50,21 @@ impl Meal for HawMeal {
         &self.contents
     }
 
-    fn load_from_local(date: NaiveDate, mensa_name: &str, cache_dir: PathBuf) -> Result<Vec<Self>, std::io::Error> {
-        // Read timestamp of local mensa data
-        let cache_dir_str = cache_dir.to_str().ok_or(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Cache directory path is not valid"))?;
-        let mut file: File = File::open(format!("{}/mensadata/timestamp", &cache_dir_str))?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let timestamp = contents.trim().parse::<i64>().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        let last_change = chrono::DateTime::from_timestamp(timestamp, 0).unwrap();
-        let last_change_date = last_change.date_naive();
-
-        // If the data is older than 1 day, fetch new data
-        if chrono::Local::now().date_naive().signed_duration_since(last_change_date) > chrono::Duration::days(1) {
-            println!("Local mensa data is outdated. Fetching new data...");
-            Self::fetch_mensa_data(&cache_dir)?;
+    fn load_from_local(date: NaiveDate, mensa_name: &str, cache_dir: PathBuf) -> Result<Vec<Self>, MealError> {
+        // Refresh the local data if it is older than the configured TTL
+        match cache::freshness(Dataset::Mensa, &Dataset::Mensa.path(&cache_dir)) {
+            Freshness::Missing if fetcher::is_offline() => return Err(MealError::Offline),
+            Freshness::Missing => return Err(MealError::NotFound("Local mensa data".to_string())),
+            Freshness::Stale(age) if fetcher::is_offline() => cache::report_stale(Dataset::Mensa, age),
+            Freshness::Stale(_) => {
+                println!("Local mensa data is outdated. Fetching new data...");
+                Self::fetch_mensa_data(&cache_dir)?;
+            }
+            Freshness::Fresh => {}
         }
 
         // Load new data
         let mensadata_path = Self::get_mensadata_dir(&cache_dir)?;
-
-        if !mensadata_path.exists() {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Local mensa data not available at {:?}", mensadata_path)));
-        }
         
         let path_str = format!("./{}/{}/{}/{}.json",
             &mensa_name,
@@ -85,7 +75,7 @@ impl Meal for HawMeal {
         let path = Path::join(&mensadata_path, Path::new(&path_str));
 
         if !path.exists() {
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("No data found for mensa '{}' on date '{}'", &mensa_name, date)));
+            return Err(MealError::NoDataForDate { mensa: mensa_name.to_string(), date });
         }
 
         // Read data
@@ -94,61 +84,42 @@ impl Meal for HawMeal {
         Ok(serde_json::from_str(&file_content)?)
     }
 
-    fn fetch_data_for_date(date: NaiveDate, mensa_name: &str) -> Result<Vec<Self>, std::io::Error> {
-        let url = format!("{DATA_URL}/{}/{}/{}/{}.json",
+    fn fetch_data_for_date(date: NaiveDate, mensa_name: &str) -> Result<Vec<Self>, MealError> {
+        let path = format!("{}/{}/{}/{}.json",
             &mensa_name,
             &date.format("%Y"),
             &date.format("%m"),
             &date.format("%d")
         );
 
-        let result = reqwest::get(url)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-
-        // Handle HTTP errors
-        if result.status().is_client_error() || result.status().is_server_error() {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, result.status().to_string()));
+        match Provider::Mensa.source().fetch_file(&path)? {
+            Some(content) => Ok(serde_json::from_str(&content)?),
+            None => Err(MealError::NoDataForDate { mensa: mensa_name.to_string(), date }),
         }
-
-        Ok(serde_json::from_str(&result.text().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?)?)
     }
 
     /// Fetches Mensadata and stores it in the cache dir
-    /// Fetch Mensa data from git repo (https://github.com/HAWHHCalendarBot/mensa-data.git) and save it locally
-    fn fetch_mensa_data(cache_dir: &PathBuf) -> Result<(), std::io::Error> {
+    /// Fetch Mensa data from the configured source (default https://github.com/HAWHHCalendarBot/mensa-data) and save it locally
+    fn fetch_mensa_data(cache_dir: &PathBuf) -> Result<(), MealError> {
         // Check locally if the data is available
         let mensadata_path = Self::get_mensadata_dir(&cache_dir)?;
 
-        // Create the mensa data directory if it doesn't exist
-        if !mensadata_path.exists() {
-            fs::create_dir_all(&mensadata_path)?;
-        } else {
-            // If the directory already exists, remove it to ensure a fresh clone
-            fs::remove_dir_all(&mensadata_path)?;
-            fs::create_dir_all(&mensadata_path)?;
-        }
+        // Download into a staging directory, the current data stays usable if the clone fails
+        cache::refresh(&mensadata_path, |staging| {
+            // Download the mensa data repository
+            let source = Provider::Mensa.source();
+            if fetcher::fetch(&source, Backend::from_config(), &mensadata_path, staging)? == Fetched::NotModified {
+                println!("Mensa data is unchanged.");
+            }
+
+            cache::write_timestamp(staging)?;
+
+            Ok(())
+        }, Self::validate_mensa_data)?;
 
-        // Clone the mensa data repository
-        let output = Command::new("git")
-            .arg("clone")
-            .arg("--depth")
-            .arg("1")
-            .arg("https://github.com/HAWHHCalendarBot/mensa-data.git")
-            .arg(&mensadata_path)
-            .output()?;
-
-        if !output.status.success() {
-            let error_message = String::from_utf8_lossy(&output.stderr);
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to clone mensa data: {}", error_message)));
-        }
-        
         // If the clone was successful, return Ok
         println!("Mensa data cloned successfully.");
 
-        // Refresh Timestamp
-        let mut file = File::create(Path::join(&mensadata_path, "./timestamp"))?;
-        file.write_all(&chrono::Local::now().timestamp().to_string().into_bytes()).expect("couldnt write timestamp");
-
         Ok(())
     }
 }
\ No newline at end of file
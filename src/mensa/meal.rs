@@ -1,14 +1,13 @@
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::{thread, thread::JoinHandle};
 
 use chrono::NaiveDate;
-use dirs::cache_dir;
 use serde::{Deserialize, Serialize};
-use std::{fmt, fs};
+use std::fmt;
 
-use crate::json_parser::Extras;
+use crate::{cache::{self, Dataset, Freshness}, fetcher, json_parser::Extras, mensa::error::MealError};
 
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub struct Meta {
@@ -23,45 +22,48 @@ pub trait Meal: Sized + Debug {
 
     //// Fetching data ////
 
-    fn get_food_for_date(date: NaiveDate, mensa_name: &str) -> Result<Vec<Self>, std::io::Error> {
+    fn get_food_for_date(date: NaiveDate, mensa_name: &str) -> Result<Vec<Self>, MealError> {
         // Check if the mensa data is available locally
         // -> if so, load it
-        // -> else load for single date directly
+        // -> else load for single date directly, unless offline
         let cache_dir = Self::get_cache_dir()?;
-        Self::load_from_local(date, mensa_name, cache_dir).or_else(|_| Self::fetch_data_for_date(date, mensa_name))
+        Self::check_mensa_known(&cache_dir, mensa_name)?;
+        let local = Self::load_from_local(date, mensa_name, cache_dir);
+        if fetcher::is_offline() {
+            return local;
+        }
+        local.or_else(|_| Self::fetch_data_for_date(date, mensa_name))
+    }
+
+    /// Returns `NotFound` if the mensa is not part of the local mensa data.
+    /// Without local data every name is accepted, the download reports unknown mensas.
+    fn check_mensa_known(cache_dir: &Path, mensa_name: &str) -> Result<(), MealError> {
+        let mensadata_path = Dataset::Mensa.path(cache_dir);
+        if cache::last_refresh(&mensadata_path).is_some() && !mensadata_path.join(mensa_name).is_dir() {
+            return Err(MealError::NotFound(format!("Mensa '{}'", mensa_name)));
+        }
+        Ok(())
     }
 
     /// Local loading ///
     
-    fn load_from_local(date: chrono::NaiveDate, mensa_name: &str, cache_dir: PathBuf) -> Result<Vec<Self>, std::io::Error> where Self: Sized;
+    fn load_from_local(date: chrono::NaiveDate, mensa_name: &str, cache_dir: PathBuf) -> Result<Vec<Self>, MealError> where Self: Sized;
 
     //// Fetching remote data ////
     
     /// Fetches data for a single date
-    fn fetch_data_for_date(date: chrono::NaiveDate, mensa_name: &str) -> Result<Vec<Self>, std::io::Error> where Self: Sized;
+    fn fetch_data_for_date(date: chrono::NaiveDate, mensa_name: &str) -> Result<Vec<Self>, MealError> where Self: Sized;
 
-    fn update_mensa_data() -> JoinHandle<Result<(), std::io::Error>> {
+    fn update_mensa_data() -> JoinHandle<Result<(), MealError>> {
         thread::spawn(|| {
             let cache_dir = Self::get_cache_dir()?;
             let mensadata_path = Self::get_mensadata_dir(&cache_dir)?;
             
-            // Check if timestamp file exists & is older than 1 day
-            let timestamp_path = mensadata_path.join("timestamp");
-
-            if !timestamp_path.exists() {
-                // If timestamp file does not exist, fetch new data
-                Self::fetch_mensa_data(&cache_dir)?;
-            }
-
-            let mut file:PathBuf = timestamp_path;
-            let contents = fs::read_to_string(&mut file)?;
-            let last_change = chrono::DateTime::from_timestamp(contents.parse().unwrap(), 0).unwrap();
-            let last_change_date = last_change.date_naive();
-
-            if chrono::Local::now().date_naive().signed_duration_since(last_change_date) <= chrono::Duration::days(1) {
+            // Only refresh missing data or data older than the configured TTL
+            if cache::freshness(Dataset::Mensa, &mensadata_path) == Freshness::Fresh {
                 return Ok(()); // Data is up-to-date
             }
-            
+
             Self::fetch_mensa_data(&cache_dir)?;
 
             Ok(())
@@ -69,23 +71,24 @@ pub trait Meal: Sized + Debug {
     }
 
     /// Fetches Mensadata and stores it in the cache dir
-    fn fetch_mensa_data(cache_dir: &PathBuf) -> Result<(), std::io::Error>;
+    fn fetch_mensa_data(cache_dir: &PathBuf) -> Result<(), MealError>;
+
+    /// Checks that downloaded mensa data contains menus before it replaces the current data.
+    fn validate_mensa_data(mensadata_path: &std::path::Path) -> Result<(), MealError> {
+        if !cache::contains_file_with_extension(mensadata_path, "json")? {
+            return Err(MealError::CacheCorrupt("downloaded mensa data contains no menus".to_string()));
+        }
+        Ok(())
+    }
 
     /// UTIL ///
     
-    fn get_cache_dir() -> Result<std::path::PathBuf, std::io::Error> {
-        let cache_dir = cache_dir().ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, "Could not find cache directory"))?;
-        Ok(cache_dir.join(env!("CARGO_PKG_NAME")))
+    fn get_cache_dir() -> Result<std::path::PathBuf, MealError> {
+        cache::cache_dir().ok_or(MealError::NotFound("Cache directory".to_string()))
     }
 
-    fn get_mensadata_dir(cache_dir: &PathBuf) -> Result<std::path::PathBuf, std::io::Error> {
-        let mensadata_path = cache_dir.join("mensadata");
-
-        if !mensadata_path.exists() {
-            fs::create_dir_all(&mensadata_path).expect("Could not create mensa data directory");
-        }
-
-        Ok(mensadata_path)
+    fn get_mensadata_dir(cache_dir: &PathBuf) -> Result<std::path::PathBuf, MealError> {
+        Ok(cache::data_dir(Dataset::Mensa, cache_dir)?)
     }
 
     fn filter_food_by_extras(
@@ -1,3 +1,4 @@
+pub(crate) mod error;
 pub(crate) mod meal;
 pub(crate) mod haw_meal;
 #[cfg(test)]
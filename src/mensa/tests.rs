@@ -10,9 +10,11 @@ mod tests {
     use tempfile::tempdir;
 
     use crate::mensa::test_meal::TestMeal;
+    use crate::mensa::error::MealError;
     use crate::mensa::meal::{Contents, Meal, Prices};
     use crate::mensa::haw_meal::HawMeal;
     use crate::json_parser::Extras;
+    use crate::json_parser::isolate_config;
 use std::fs;
 use std::io;
 
@@ -57,6 +59,7 @@ use std::io;
     #[test]
     fn test_load_local_data() {
         // arrange
+        isolate_config();
         let test_meal = standard_meal();
 
         let test_path = PathBuf::from("./test_data");
@@ -79,6 +82,7 @@ use std::io;
     #[test]
     fn test_load_local_data_invalid_date() {
         // arrange
+        isolate_config();
         let test_path = PathBuf::from("./test_data");
 
         // act
@@ -88,9 +92,57 @@ use std::io;
         assert!(result.is_err(), "Expected error when loading local data with invalid date");
     }
 
+    #[test]
+    fn test_unknown_mensa() {
+        // arrange
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path();
+
+        // act
+        let without_data = HawMeal::check_mensa_known(test_path, "Unbekannte Mensa");
+        let dst = copy_testdata_into(test_path);
+        fs::write(dst.join("timestamp"), chrono::Local::now().timestamp().to_string()).unwrap();
+        let known = HawMeal::check_mensa_known(test_path, "TestMensa");
+        let unknown = HawMeal::check_mensa_known(test_path, "Unbekannte Mensa");
+
+        // assert
+        assert!(without_data.is_ok(), "Without local data the name cannot be checked");
+        assert!(known.is_ok());
+        match unknown {
+            Err(e @ MealError::NotFound(_)) => assert_eq!(e.exit_code(), 3),
+            other => panic!("Expected NotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_local_data_errors() {
+        // arrange
+        isolate_config();
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+
+        // act
+        let missing = TestMeal::load_from_local(date, "TestMensa", test_path.to_path_buf());
+        let dst = copy_testdata_into(test_path);
+        fs::write(dst.join("timestamp"), chrono::Local::now().timestamp().to_string()).unwrap();
+        let result = TestMeal::load_from_local(date, "TestMensa", test_path.to_path_buf());
+
+        // assert
+        assert!(matches!(missing, Err(MealError::NotFound(_))), "Expected NotFound, got {:?}", missing);
+        match result {
+            Err(e @ MealError::NoDataForDate { .. }) => {
+                assert_eq!(e.exit_code(), 4);
+                assert!(e.to_string().contains("holiday"), "Message should explain closed days: {}", e);
+            }
+            other => panic!("Expected NoDataForDate, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_load_local_data_no_data() {
         // arrange
+        isolate_config();
         let invalid_path = PathBuf::from("./non_existent_data");
 
         // act
@@ -165,6 +217,7 @@ use std::io;
     #[test]
     fn test_load_local_data_testdata() {
         // arrange
+        isolate_config();
         let test_meal = test_meal();
         let temp_dir = tempdir().unwrap();
         let test_path = temp_dir.path();
@@ -182,6 +235,7 @@ use std::io;
     #[test]
     fn test_load_local_data_invalid_date_testdata() {
         // arrange
+        isolate_config();
         let temp_dir = tempdir().unwrap();
         let test_path = temp_dir.path();
         // copy test data to temp directory
@@ -195,6 +249,7 @@ use std::io;
     #[test]
     fn test_load_local_data_no_data_testdata() {
         // arrange
+        isolate_config();
         let temp_dir = tempdir().unwrap();
         let invalid_path = temp_dir.path().join("non_existent_data");
         // act
@@ -0,0 +1,343 @@
+use std::fmt;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::json_parser::Config;
+
+/// Number of weeks with lectures in a semester, lecture-free weeks are not counted.
+const LECTURE_WEEKS: i64 = 16;
+
+/// Hamburg public holiday.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: &'static str,
+}
+
+/// Returns easter sunday of the year (Gregorian calendar, anonymous algorithm).
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter is always a valid date")
+}
+
+/// Returns the public holidays in Hamburg of the year, sorted by date.
+pub fn holidays(year: i32) -> Vec<Holiday> {
+    let date = |month, day| NaiveDate::from_ymd_opt(year, month, day).expect("Holidays are valid dates");
+    let easter = easter_sunday(year);
+
+    let mut holidays = vec![
+        Holiday { date: date(1, 1), name: "Neujahr" },
+        Holiday { date: easter - Duration::days(2), name: "Karfreitag" },
+        Holiday { date: easter + Duration::days(1), name: "Ostermontag" },
+        Holiday { date: date(5, 1), name: "Tag der Arbeit" },
+        Holiday { date: easter + Duration::days(39), name: "Christi Himmelfahrt" },
+        Holiday { date: easter + Duration::days(50), name: "Pfingstmontag" },
+        Holiday { date: date(10, 3), name: "Tag der Deutschen Einheit" },
+        Holiday { date: date(12, 25), name: "1. Weihnachtstag" },
+        Holiday { date: date(12, 26), name: "2. Weihnachtstag" },
+    ];
+    // Public holiday in Hamburg since 2018
+    if year >= 2018 {
+        holidays.push(Holiday { date: date(10, 31), name: "Reformationstag" });
+    }
+
+    holidays.sort_by_key(|holiday| holiday.date);
+    holidays
+}
+
+/// Returns the Hamburg public holiday on the date, if any.
+pub fn holiday(date: NaiveDate) -> Option<Holiday> {
+    holidays(date.year()).into_iter().find(|holiday| holiday.date == date)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Term {
+    /// Sommersemester, March to August.
+    Summer,
+    /// Wintersemester, September to February.
+    Winter,
+}
+
+/// Semester with its lecture period, following the usual HAW Hamburg schedule:
+/// lectures start in the second half of March or September and last 16 weeks,
+/// the two weeks around christmas and new year are lecture-free.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Semester {
+    pub term: Term,
+    /// The year the semester starts in.
+    pub year: i32,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Monday of the first lecture week.
+    pub lectures_start: NaiveDate,
+    /// Friday of the last lecture week.
+    pub lectures_end: NaiveDate,
+    /// Lecture-free weeks within the lecture period (first and last day).
+    pub breaks: Vec<(NaiveDate, NaiveDate)>,
+}
+
+impl Semester {
+    pub fn new(term: Term, year: i32) -> Semester {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).expect("Semester dates are valid");
+
+        let (start, end, lectures_start, breaks) = match term {
+            Term::Summer => (date(year, 3, 1), date(year, 8, 31), next_monday(date(year, 3, 15)), vec![]),
+            Term::Winter => {
+                let christmas_break = (monday_of(date(year, 12, 24)), monday_of(date(year + 1, 1, 1)) + Duration::days(6));
+                (
+                    date(year, 9, 1),
+                    date(year + 1, 3, 1).pred_opt().expect("February has a last day"),
+                    next_monday(date(year, 9, 22)),
+                    vec![christmas_break],
+                )
+            }
+        };
+
+        let break_weeks: i64 = breaks.iter().map(|(first, last)| (*last - *first).num_days() / 7 + 1).sum();
+        let lectures_end = lectures_start + Duration::weeks(LECTURE_WEEKS + break_weeks) - Duration::days(3);
+
+        Semester { term, year, start, end, lectures_start, lectures_end, breaks }
+    }
+
+    /// Returns the semester the date belongs to.
+    pub fn containing(date: NaiveDate) -> Semester {
+        match date.month() {
+            3..=8 => Semester::new(Term::Summer, date.year()),
+            9..=12 => Semester::new(Term::Winter, date.year()),
+            _ => Semester::new(Term::Winter, date.year() - 1),
+        }
+    }
+
+    /// Returns the semester the date belongs to, with the lecture period from
+    /// `mensa settings semester` if it was configured for this semester.
+    pub fn configured(date: NaiveDate) -> Semester {
+        let semester = Semester::containing(date);
+        match Config::load_config().get_semester_dates() {
+            Some((start, end)) if Semester::containing(start).name_eq(&semester) => semester.with_lectures(start, end),
+            _ => semester,
+        }
+    }
+
+    /// Returns the semester whose lectures are running or start next, e.g. the winter semester in August.
+    pub fn upcoming(date: NaiveDate) -> Semester {
+        let semester = Semester::configured(date);
+        if date <= semester.lectures_end {
+            return semester;
+        }
+        Semester::configured(semester.end + Duration::days(1))
+    }
+
+    /// Replaces the lecture period, breaks outside of it are dropped.
+    pub fn with_lectures(mut self, start: NaiveDate, end: NaiveDate) -> Semester {
+        self.lectures_start = monday_of(start);
+        self.lectures_end = end;
+        self.breaks.retain(|(first, last)| *first <= end && *last >= start);
+        self
+    }
+
+    fn name_eq(&self, other: &Semester) -> bool {
+        self.term == other.term && self.year == other.year
+    }
+
+    /// Returns every date with lectures on the weekday, skipping lecture-free weeks,
+    /// public holidays and the weeks that do not match the parity.
+    pub fn lecture_dates(&self, weekday: Weekday, parity: WeekParity) -> Vec<NaiveDate> {
+        let first = self.lectures_start + Duration::days(weekday.num_days_from_monday().into());
+
+        first
+            .iter_weeks()
+            .take_while(|date| *date <= self.lectures_end + Duration::days(2))
+            .filter(|date| holiday(*date).is_none())
+            .filter(|date| self.lecture_week(*date).is_some_and(|week| parity.matches(week)))
+            .collect()
+    }
+
+    /// Returns the number of the lecture week of the date, starting at 1.
+    /// Lecture-free weeks are skipped, outside the lecture period there is no lecture week.
+    pub fn lecture_week(&self, date: NaiveDate) -> Option<u32> {
+        if date < self.lectures_start || date > self.lectures_end + Duration::days(2) {
+            return None;
+        }
+        if self.breaks.iter().any(|(first, last)| *first <= date && date <= *last) {
+            return None;
+        }
+
+        let monday = monday_of(date);
+        let break_weeks: i64 = self
+            .breaks
+            .iter()
+            .filter(|(_, last)| *last < monday)
+            .map(|(first, last)| (*last - *first).num_days() / 7 + 1)
+            .sum();
+
+        Some(((monday - self.lectures_start).num_weeks() - break_weeks + 1) as u32)
+    }
+}
+
+impl fmt::Display for Semester {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.term {
+            Term::Summer => write!(f, "SoSe {}", self.year),
+            Term::Winter => write!(f, "WiSe {}/{:02}", self.year, (self.year + 1) % 100),
+        }
+    }
+}
+
+/// Weeks a lecture takes place in. Plans mark alternating lectures as A or B week,
+/// A weeks are the odd lecture weeks (1, 3, 5, ...), B weeks the even ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekParity {
+    Every,
+    A,
+    B,
+}
+
+impl WeekParity {
+    pub fn matches(&self, lecture_week: u32) -> bool {
+        match self {
+            WeekParity::Every => true,
+            WeekParity::A => !lecture_week.is_multiple_of(2),
+            WeekParity::B => lecture_week.is_multiple_of(2),
+        }
+    }
+}
+
+/// Describes the date within the semester for day headers,
+/// e.g. "Vorlesungswoche 5", "Feiertag: Karfreitag" or "vorlesungsfrei".
+pub fn describe_day(date: NaiveDate) -> String {
+    describe_day_in(date, &Semester::configured(date))
+}
+
+/// Describes the date within the given semester, see `describe_day`.
+fn describe_day_in(date: NaiveDate, semester: &Semester) -> String {
+    if let Some(holiday) = holiday(date) {
+        return format!("Feiertag: {}", holiday.name);
+    }
+
+    match semester.lecture_week(date) {
+        Some(week) => format!("Vorlesungswoche {}", week),
+        None => "vorlesungsfrei".to_string(),
+    }
+}
+
+/// Returns the monday of the week of the date.
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+/// Returns the date if it is a monday, otherwise the following monday.
+fn next_monday(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Mon => date,
+        _ => monday_of(date) + Duration::weeks(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+    }
+
+    #[test]
+    fn test_holidays() {
+        let holidays = holidays(2025);
+
+        assert_eq!(holidays.len(), 10);
+        assert_eq!(holiday(date(2025, 4, 18)).map(|holiday| holiday.name), Some("Karfreitag"));
+        assert_eq!(holiday(date(2025, 5, 29)).map(|holiday| holiday.name), Some("Christi Himmelfahrt"));
+        assert_eq!(holiday(date(2025, 10, 31)).map(|holiday| holiday.name), Some("Reformationstag"));
+        assert_eq!(holiday(date(2017, 10, 31)), None);
+        assert_eq!(holiday(date(2025, 4, 17)), None);
+    }
+
+    #[test]
+    fn test_summer_semester() {
+        let semester = Semester::containing(date(2025, 5, 1));
+
+        assert_eq!(semester.to_string(), "SoSe 2025");
+        assert_eq!(semester.lectures_start, date(2025, 3, 17));
+        assert_eq!(semester.lectures_end, date(2025, 7, 4));
+        assert_eq!(semester.lecture_week(date(2025, 3, 17)), Some(1));
+        assert_eq!(semester.lecture_week(date(2025, 4, 16)), Some(5));
+        assert_eq!(semester.lecture_week(date(2025, 7, 4)), Some(16));
+        assert_eq!(semester.lecture_week(date(2025, 3, 14)), None);
+        assert_eq!(semester.lecture_week(date(2025, 7, 7)), None);
+    }
+
+    #[test]
+    fn test_winter_semester_skips_christmas_break() {
+        let semester = Semester::containing(date(2026, 1, 15));
+
+        assert_eq!(semester.to_string(), "WiSe 2025/26");
+        assert_eq!(semester.lectures_start, date(2025, 9, 22));
+        assert_eq!(semester.breaks, vec![(date(2025, 12, 22), date(2026, 1, 4))]);
+        assert_eq!(semester.lecture_week(date(2025, 12, 19)), Some(13));
+        assert_eq!(semester.lecture_week(date(2025, 12, 30)), None);
+        assert_eq!(semester.lecture_week(date(2026, 1, 5)), Some(14));
+        assert_eq!(semester.lectures_end, date(2026, 1, 23));
+        assert_eq!(semester.lecture_week(date(2026, 1, 23)), Some(16));
+    }
+
+    #[test]
+    fn test_lecture_dates() {
+        let semester = Semester::containing(date(2025, 10, 1));
+
+        let mondays = semester.lecture_dates(Weekday::Mon, WeekParity::Every);
+        let fridays_a = semester.lecture_dates(Weekday::Fri, WeekParity::A);
+        let fridays_b = semester.lecture_dates(Weekday::Fri, WeekParity::B);
+
+        assert_eq!(mondays.len(), 16);
+        assert_eq!(mondays.first(), Some(&date(2025, 9, 22)));
+        assert_eq!(mondays.last(), Some(&date(2026, 1, 19)));
+        assert!(!mondays.contains(&date(2025, 12, 22)));
+        assert_eq!(&fridays_a[..3], &[date(2025, 9, 26), date(2025, 10, 10), date(2025, 10, 24)]);
+        // Fridays of lecture weeks 2 and 6 are public holidays
+        assert_eq!(&fridays_b[..2], &[date(2025, 10, 17), date(2025, 11, 14)]);
+    }
+
+    #[test]
+    fn test_with_lectures() {
+        let semester = Semester::containing(date(2025, 10, 1)).with_lectures(date(2025, 10, 1), date(2025, 12, 19));
+
+        assert_eq!(semester.lectures_start, date(2025, 9, 29));
+        assert!(semester.breaks.is_empty());
+        assert_eq!(semester.lecture_week(date(2025, 10, 6)), Some(2));
+        assert_eq!(semester.lecture_week(date(2026, 1, 5)), None);
+    }
+
+    #[test]
+    fn test_describe_day() {
+        let describe = |date: NaiveDate| describe_day_in(date, &Semester::containing(date));
+
+        assert_eq!(describe(date(2025, 10, 3)), "Feiertag: Tag der Deutschen Einheit");
+        assert_eq!(describe(date(2025, 10, 20)), "Vorlesungswoche 5");
+        assert_eq!(describe(date(2025, 8, 4)), "vorlesungsfrei");
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    events::ics_export,
+    fetcher,
+    mensa::{error::MealError, haw_meal::HawMeal, meal::Meal},
+};
+
+/// Interval in which the mensa data is checked for updates.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Time a client may take to send its request or to receive the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections handled at the same time, further clients are answered with 503.
+const MAX_CONNECTIONS: usize = 32;
+
+/// Number of connections currently handled.
+static ACTIVE_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Slot of a handled connection, released when the handler is done.
+struct ConnectionSlot;
+
+impl ConnectionSlot {
+    /// Takes a slot, `None` if `MAX_CONNECTIONS` are already handled.
+    fn acquire() -> Option<ConnectionSlot> {
+        if ACTIVE_CONNECTIONS.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot)
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        ACTIVE_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// HTTP response with status line, content type and body.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Self {
+        Response { status: 200, content_type, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", message),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Routes of the feed server.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Route {
+    /// `/calendar.ics`
+    Calendar,
+    /// `/mensa/<name>/<date>.json`
+    Mensa { name: String, date: NaiveDate },
+}
+
+impl Route {
+    /// Parses the request path into a route.
+    pub fn parse(path: &str) -> Result<Route, Response> {
+        // Ignore query strings, calendar apps like to append cache busters
+        let path = path.split('?').next().unwrap_or_default();
+
+        if path == "/calendar.ics" {
+            return Ok(Route::Calendar);
+        }
+
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["mensa", name, file] => {
+                let date = file
+                    .strip_suffix(".json")
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                    .ok_or_else(|| Response::error(400, "Expected /mensa/<name>/<YYYY-MM-DD>.json"))?;
+                let name = percent_decode(name)
+                    .filter(|name| !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']))
+                    .ok_or_else(|| Response::error(400, "Invalid mensa name"))?;
+
+                Ok(Route::Mensa { name, date })
+            }
+            _ => Err(Response::error(404, "Not found")),
+        }
+    }
+
+    /// Builds the response for the route from the local caches.
+    fn respond(&self) -> Response {
+        match self {
+            Route::Calendar => match ics_export::configured_calendar() {
+                Ok((calendar, _)) => Response::ok("text/calendar; charset=utf-8", calendar),
+                Err(e) => Response::error(500, &e.to_string()),
+            },
+            Route::Mensa { name, date } => {
+                let food: Result<Vec<HawMeal>, MealError> = Meal::get_food_for_date(*date, name);
+                match food.map(|food| serde_json::to_string(&food)) {
+                    Ok(Ok(json)) => Response::ok("application/json", json),
+                    Ok(Err(e)) => Response::error(500, &e.to_string()),
+                    Err(e @ MealError::NoDataForDate { .. }) => Response::error(404, &e.to_string()),
+                    Err(e) => Response::error(500, &e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// Serves the calendar and mensa feeds until the process is stopped.
+/// Every connection is handled on its own thread, at most `MAX_CONNECTIONS` at a time.
+/// The caches are refreshed in the background.
+pub fn serve(host: &str, port: u16) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind((host, port))?;
+    println!("Serving on http://{}:{}", host, port);
+    println!("  Calendar: http://{}:{}/calendar.ics", host, port);
+    println!("  Mensa:    http://{}:{}/mensa/<name>/<YYYY-MM-DD>.json", host, port);
+
+    // Keep the mensa data fresh, event data is refreshed on access by `Event::load_from_local`
+    if !fetcher::is_offline() {
+        thread::spawn(|| loop {
+            if let Ok(Err(e)) = HawMeal::update_mensa_data().join() {
+                eprintln!("Error updating mensa data: {}", e);
+            }
+            thread::sleep(REFRESH_INTERVAL);
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                // Slow or idle clients must not keep a handler forever
+                let timeouts = stream
+                    .set_read_timeout(Some(CLIENT_TIMEOUT))
+                    .and_then(|_| stream.set_write_timeout(Some(CLIENT_TIMEOUT)));
+                if let Err(e) = timeouts {
+                    eprintln!("Error setting up connection: {}", e);
+                    continue;
+                }
+
+                let Some(slot) = ConnectionSlot::acquire() else {
+                    let response = Response::error(503, "Too many connections, try again later");
+                    if let Err(e) = write_response(&mut stream, &response, true) {
+                        eprintln!("Error rejecting connection: {}", e);
+                    }
+                    continue;
+                };
+
+                thread::spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = handle_connection(stream) {
+                        eprintln!("Error handling request: {}", e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Error accepting connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Skip the headers, requests have no body we care about
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let response = match method {
+        "GET" | "HEAD" => Route::parse(path).map_or_else(|error| error, |route| route.respond()),
+        _ => Response::error(405, "Only GET and HEAD are supported"),
+    };
+
+    println!("{} {} -> {}", method, path, response.status);
+
+    write_response(&mut stream, &response, method != "HEAD")?;
+    Ok(())
+}
+
+/// Writes the response, the body is left out for HEAD requests.
+fn write_response(stream: &mut impl Write, response: &Response, with_body: bool) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    )?;
+    if with_body {
+        stream.write_all(response.body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+/// Decodes percent-encoded URL path segments (e.g. "Mensa%20Berliner%20Tor").
+pub fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = segment.get(i + 1..i + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_parse() {
+        assert_eq!(Route::parse("/calendar.ics"), Ok(Route::Calendar));
+        assert_eq!(Route::parse("/calendar.ics?nocache=1"), Ok(Route::Calendar));
+        assert_eq!(
+            Route::parse("/mensa/Mensa%20Berliner%20Tor/2025-07-15.json"),
+            Ok(Route::Mensa {
+                name: "Mensa Berliner Tor".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 7, 15).unwrap(),
+            })
+        );
+        assert_eq!(Route::parse("/mensa/Mensa/15.07.2025.json").unwrap_err().status, 400);
+        assert_eq!(Route::parse("/mensa/..%2F..%2Fetc/2025-07-15.json").unwrap_err().status, 400);
+        assert_eq!(Route::parse("/unknown").unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let slots: Vec<ConnectionSlot> = (0..MAX_CONNECTIONS).map(|_| ConnectionSlot::acquire().unwrap()).collect();
+        assert!(ConnectionSlot::acquire().is_none());
+
+        drop(slots);
+        assert!(ConnectionSlot::acquire().is_some());
+    }
+
+    #[test]
+    fn test_write_response() {
+        let mut written = Vec::new();
+        write_response(&mut written, &Response::error(503, "Too many connections"), false).unwrap();
+
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(written.contains("Content-Length: 21\r\n"));
+        assert!(written.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("Mensa%20Armgartstra%C3%9Fe"), Some("Mensa Armgartstraße".to_string()));
+        assert_eq!(percent_decode("broken%2"), None);
+    }
+}